@@ -0,0 +1,149 @@
+// Benchmarks of the three steps of a real proof: keygen_pk, create_proof and verify_proof.
+// `cargo bench -p chips` runs all of them, `cargo bench -p chips -- range_check` only the
+// circuits whose name matches. Each circuit is measured at several sizes, so a regression shows
+// up as one size getting slower and the layouts can be compared on the same n.
+use std::fmt::Display;
+
+use chips::{
+    fibonacci::{fibonacci, DoublingCircuit, FiboCircuit, FibonacciCircuit},
+    is_zero::function::FunctionCircuit,
+    prover,
+    range_check::RangeCheckConfig,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use halo2_proofs::{
+    circuit::{floor_planner::V1, Layouter, Value},
+    pasta::{EqAffine, Fp},
+    plonk::{keygen_pk, keygen_vk, Assigned, Circuit, ConstraintSystem, Error},
+    poly::commitment::Params,
+};
+
+// Benchmarks keygen_pk, create_proof and verify_proof for the circuit built by `circuit`, one
+// criterion group per step so the same step of every circuit is reported together
+fn bench<C: Circuit<Fp>>(
+    c: &mut Criterion,
+    name: &str,
+    size: impl Display,
+    k: u32,
+    circuit: impl Fn() -> C,
+    instances: &[&[Fp]],
+) {
+    let params: Params<EqAffine> = Params::new(k);
+    let empty_circuit = circuit().without_witnesses();
+    let vk = keygen_vk(&params, &empty_circuit).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &empty_circuit).unwrap();
+    let proof = prover::prove(&params, &pk, circuit(), instances).unwrap();
+    let id = BenchmarkId::new(name, size);
+
+    let mut group = c.benchmark_group("keygen_pk");
+    group.sample_size(10);
+    group.bench_function(id.clone(), |b| {
+        b.iter(|| keygen_pk(&params, vk.clone(), &empty_circuit).unwrap())
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("create_proof");
+    group.sample_size(10);
+    group.bench_function(id.clone(), |b| {
+        b.iter(|| prover::prove(&params, &pk, circuit(), instances).unwrap())
+    });
+    group.finish();
+
+    let mut group = c.benchmark_group("verify_proof");
+    group.bench_function(id, |b| {
+        b.iter(|| prover::verify(&params, pk.get_vk(), instances, &proof).unwrap())
+    });
+    group.finish();
+}
+
+const LENGTHS: [usize; 3] = [16, 128, 1024];
+
+fn fibonacci_chips(c: &mut Criterion) {
+    let (one, zero) = (Fp::one(), Fp::zero());
+
+    for n in LENGTHS {
+        let circuit = FiboCircuit::new(Value::known(one), Value::known(one), n);
+        let public_input = circuit.public_input(one, one);
+        let k = circuit.k();
+        bench(c, "fibonacci/three_column", n, k, || FiboCircuit::new(Value::known(one), Value::known(one), n), &[&public_input]);
+
+        let circuit = FibonacciCircuit::new(n);
+        let public_input = circuit.public_input(one, one);
+        bench(c, "fibonacci/single_column", n, circuit.k(), || FibonacciCircuit::new(n), &[&public_input]);
+
+        // enough bits for the largest length, so the doubling circuit has the same size for all n
+        let bits = 11;
+        let public_input = vec![Fp::from(n as u64), fibonacci(zero, one, n)];
+        let k = DoublingCircuit::<Fp>::new(Value::unknown(), bits).k();
+        bench(c, "fibonacci/doubling", n, k, || DoublingCircuit::new(Value::known(n as u64), bits), &[&public_input]);
+    }
+}
+
+// VALUES values, all checked with the range check gate or all with the lookup
+struct RangeCheckPath<const RANGE: usize, const LOOKUP_RANGE: usize, const LOOKUP: bool> {
+    values: Vec<Value<Assigned<Fp>>>,
+}
+
+const VALUES: usize = 64;
+
+impl<const RANGE: usize, const LOOKUP_RANGE: usize, const LOOKUP: bool> RangeCheckPath<RANGE, LOOKUP_RANGE, LOOKUP> {
+    fn new() -> Self {
+        let range = if LOOKUP { LOOKUP_RANGE } else { RANGE };
+        let values = (0..VALUES).map(|i| Value::known(Fp::from((i % range) as u64).into())).collect();
+        Self { values }
+    }
+
+    // the table fills LOOKUP_RANGE rows, and the values are laid out next to it
+    fn k() -> u32 {
+        prover::min_k::<Fp, Self>(std::cmp::max(LOOKUP_RANGE, VALUES))
+    }
+}
+
+impl<const RANGE: usize, const LOOKUP_RANGE: usize, const LOOKUP: bool> Circuit<Fp> for RangeCheckPath<RANGE, LOOKUP_RANGE, LOOKUP> {
+    type Config = RangeCheckConfig<Fp, RANGE, LOOKUP_RANGE>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self { values: vec![Value::unknown(); VALUES] }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckConfig::configure(meta, value)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+        config.table.load(&mut layouter)?;
+        // assign takes the gate up to RANGE and the lookup above it
+        let range = if LOOKUP { LOOKUP_RANGE } else { 0 };
+        for value in &self.values {
+            config.assign(layouter.namespace(|| "value"), *value, range)?;
+        }
+        Ok(())
+    }
+}
+
+fn range_check_path<const RANGE: usize, const LOOKUP_RANGE: usize, const LOOKUP: bool>(c: &mut Criterion) {
+    let (name, size) = if LOOKUP { ("range_check/lookup", LOOKUP_RANGE) } else { ("range_check/gate", RANGE) };
+    let k = RangeCheckPath::<RANGE, LOOKUP_RANGE, LOOKUP>::k();
+    bench(c, name, size, k, RangeCheckPath::<RANGE, LOOKUP_RANGE, LOOKUP>::new, &[]);
+}
+
+fn range_check(c: &mut Criterion) {
+    // the degree of the gate grows with RANGE
+    range_check_path::<4, 256, false>(c);
+    range_check_path::<8, 256, false>(c);
+    range_check_path::<16, 256, false>(c);
+    // the table grows with LOOKUP_RANGE
+    range_check_path::<4, 256, true>(c);
+    range_check_path::<4, 1024, true>(c);
+    range_check_path::<4, 4096, true>(c);
+}
+
+fn function(c: &mut Criterion) {
+    let circuit = || FunctionCircuit { a: Fp::from(10), b: Fp::from(12), c: Fp::from(15) };
+    bench(c, "is_zero/function", "a!=b", 4, circuit, &[]);
+}
+
+criterion_group!(benches, fibonacci_chips, range_check, function);
+criterion_main!(benches);
@@ -0,0 +1,421 @@
+// On-disk artifacts for handing a proof to another process.
+// Every file starts with the same header:
+//
+//   magic "H2AF" | version u8 | kind u8 | name len u16 | circuit name | k u32 | vk digest [u8; 32]
+//
+// followed by the payload of its kind:
+//   Params       -> `Params::write`
+//   VerifyingKey -> nothing: halo2 can't deserialize a verifying key, so the verifier regenerates it
+//                   from the params and the witness-free circuit and checks it against the digest
+//   Proof        -> the instance columns (count u32, then len u32 + 32-byte field elements per column)
+//                   and the transcript bytes (len u32 + bytes)
+// All integers are little endian.
+use std::{
+    error, fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use halo2_proofs::{
+    pasta::{group::ff::PrimeField, EqAffine, Fp},
+    plonk::{keygen_vk, Circuit, VerifyingKey},
+    poly::commitment::Params,
+};
+
+const MAGIC: [u8; 4] = *b"H2AF";
+const VERSION: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArtifactKind {
+    Params = 0,
+    VerifyingKey = 1,
+    Proof = 2,
+}
+
+impl ArtifactKind {
+    fn from_u8(byte: u8) -> io::Result<Self> {
+        match byte {
+            0 => Ok(ArtifactKind::Params),
+            1 => Ok(ArtifactKind::VerifyingKey),
+            2 => Ok(ArtifactKind::Proof),
+            _ => Err(invalid_data(format!("unknown artifact kind {}", byte))),
+        }
+    }
+}
+
+// The params passed to read_vk are for another k than the artifact. Returned as the inner error
+// of an io::Error of kind InvalidData
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KMismatch {
+    pub artifact: u32,
+    pub params: u32,
+}
+
+impl fmt::Display for KMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the artifact is for k = {}, the params for k = {}", self.artifact, self.params)
+    }
+}
+
+impl error::Error for KMismatch {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub kind: ArtifactKind,
+    pub circuit: String,
+    pub k: u32,
+    pub vk_digest: [u8; 32],
+}
+
+impl Header {
+    pub fn new(kind: ArtifactKind, circuit: &str, k: u32, vk: &VerifyingKey<EqAffine>) -> Self {
+        Self {
+            kind,
+            circuit: circuit.to_string(),
+            k,
+            vk_digest: vk_digest(vk),
+        }
+    }
+
+    fn write<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let name = self.circuit.as_bytes();
+        let name_len = u16::try_from(name.len())
+            .map_err(|_| invalid_data("circuit name is too long".to_string()))?;
+
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&[VERSION, self.kind as u8])?;
+        writer.write_all(&name_len.to_le_bytes())?;
+        writer.write_all(name)?;
+        writer.write_all(&self.k.to_le_bytes())?;
+        writer.write_all(&self.vk_digest)
+    }
+
+    fn read<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(invalid_data("not a halo2 artifact".to_string()));
+        }
+
+        let mut version_kind = [0u8; 2];
+        reader.read_exact(&mut version_kind)?;
+        if version_kind[0] != VERSION {
+            return Err(invalid_data(format!(
+                "unsupported artifact version {}",
+                version_kind[0]
+            )));
+        }
+        let kind = ArtifactKind::from_u8(version_kind[1])?;
+
+        let mut name_len = [0u8; 2];
+        reader.read_exact(&mut name_len)?;
+        let name = read_bytes(reader, u16::from_le_bytes(name_len) as u32)?;
+        let circuit = String::from_utf8(name)
+            .map_err(|_| invalid_data("circuit name is not utf-8".to_string()))?;
+
+        let k = read_u32(reader)?;
+
+        let mut vk_digest = [0u8; 32];
+        reader.read_exact(&mut vk_digest)?;
+
+        Ok(Self {
+            kind,
+            circuit,
+            k,
+            vk_digest,
+        })
+    }
+
+    // Reads a header and checks that it announces the kind of artifact we expect.
+    fn read_kind<R: Read>(reader: &mut R, kind: ArtifactKind) -> io::Result<Self> {
+        let header = Self::read(reader)?;
+        if header.kind != kind {
+            return Err(invalid_data(format!(
+                "expected a {:?} artifact, found {:?}",
+                kind, header.kind
+            )));
+        }
+        Ok(header)
+    }
+}
+
+// A proof together with the public inputs it was created for.
+#[derive(Debug, Clone)]
+pub struct ProofArtifact {
+    pub header: Header,
+    pub instances: Vec<Vec<Fp>>,
+    pub proof: Vec<u8>,
+}
+
+impl ProofArtifact {
+    // The instances in the shape expected by `prover::verify`.
+    pub fn instance_slices(&self) -> Vec<&[Fp]> {
+        self.instances.iter().map(|column| column.as_slice()).collect()
+    }
+}
+
+// The verifying key commits to the whole circuit shape (gates, columns, fixed commitments,
+// permutation), so two keys with the same digest verify the same statements.
+pub fn vk_digest(vk: &VerifyingKey<EqAffine>) -> [u8; 32] {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(32)
+        .personal(b"H2AF-VK-Digest")
+        .hash(format!("{:?}", vk.pinned()).as_bytes());
+
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(hash.as_bytes());
+    digest
+}
+
+pub fn write_params(
+    path: impl AsRef<Path>,
+    circuit: &str,
+    k: u32,
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    Header::new(ArtifactKind::Params, circuit, k, vk).write(&mut writer)?;
+    params.write(&mut writer)?;
+    writer.flush()
+}
+
+pub fn read_params(path: impl AsRef<Path>) -> io::Result<(Header, Params<EqAffine>)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let header = Header::read_kind(&mut reader, ArtifactKind::Params)?;
+    let params = Params::read(&mut reader)?;
+    Ok((header, params))
+}
+
+pub fn write_vk(
+    path: impl AsRef<Path>,
+    circuit: &str,
+    k: u32,
+    vk: &VerifyingKey<EqAffine>,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    Header::new(ArtifactKind::VerifyingKey, circuit, k, vk).write(&mut writer)?;
+    writer.flush()
+}
+
+// The VerifyingKey artifact only stores the digest of the key, not the key itself. The verifier
+// rebuilds the same circuit (for the Fibonacci circuits, with the same n) and this regenerates
+// its verifying key (no witnesses are needed, only its shape) and checks it against the digest.
+// Params for another k than the artifact's fail with a KMismatch before any key is generated.
+pub fn read_vk<C: Circuit<Fp>>(
+    path: impl AsRef<Path>,
+    params: &Params<EqAffine>,
+    circuit: &C,
+) -> io::Result<(Header, VerifyingKey<EqAffine>)> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let header = Header::read_kind(&mut reader, ArtifactKind::VerifyingKey)?;
+    let params_k = params_k(params);
+    if header.k != params_k {
+        let mismatch = KMismatch { artifact: header.k, params: params_k };
+        return Err(io::Error::new(io::ErrorKind::InvalidData, mismatch));
+    }
+
+    let vk = keygen_vk(params, &circuit.without_witnesses()).map_err(io::Error::other)?;
+    if vk_digest(&vk) != header.vk_digest {
+        return Err(invalid_data(format!(
+            "the circuit does not match the verifying key of {}",
+            header.circuit
+        )));
+    }
+    Ok((header, vk))
+}
+
+pub fn write_proof(
+    path: impl AsRef<Path>,
+    circuit: &str,
+    k: u32,
+    vk: &VerifyingKey<EqAffine>,
+    instances: &[&[Fp]],
+    proof: &[u8],
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    Header::new(ArtifactKind::Proof, circuit, k, vk).write(&mut writer)?;
+
+    write_len(&mut writer, instances.len())?;
+    for column in instances {
+        write_len(&mut writer, column.len())?;
+        for value in column.iter() {
+            writer.write_all(value.to_repr().as_ref())?;
+        }
+    }
+
+    write_len(&mut writer, proof.len())?;
+    writer.write_all(proof)?;
+    writer.flush()
+}
+
+pub fn read_proof(path: impl AsRef<Path>) -> io::Result<ProofArtifact> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let header = Header::read_kind(&mut reader, ArtifactKind::Proof)?;
+
+    // The lengths come from the file, nothing is allocated from them up front: a truncated or
+    // forged length runs into the end of the file instead
+    let num_columns = read_u32(&mut reader)?;
+    let mut instances = Vec::new();
+    for _ in 0..num_columns {
+        let len = read_u32(&mut reader)?;
+        let mut column = Vec::new();
+        for _ in 0..len {
+            let mut repr = [0u8; 32];
+            reader.read_exact(&mut repr)?;
+            let value: Option<Fp> = Fp::from_repr(repr).into();
+            column.push(value.ok_or_else(|| invalid_data("non-canonical field element".to_string()))?);
+        }
+        instances.push(column);
+    }
+
+    let len = read_u32(&mut reader)?;
+    let proof = read_bytes(&mut reader, len)?;
+
+    Ok(ProofArtifact {
+        header,
+        instances,
+        proof,
+    })
+}
+
+// halo2_proofs 0.2.0 keeps k private, the params hold 2^k generators
+fn params_k(params: &Params<EqAffine>) -> u32 {
+    params.get_g().len().trailing_zeros()
+}
+
+fn write_len<W: Write>(writer: &mut W, len: usize) -> io::Result<()> {
+    let len = u32::try_from(len).map_err(|_| invalid_data("length overflows u32".to_string()))?;
+    writer.write_all(&len.to_le_bytes())
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+// Reads `len` bytes, the buffer grows with what is actually read
+fn read_bytes<R: Read>(reader: &mut R, len: u32) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len as usize {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "truncated artifact"));
+    }
+    Ok(bytes)
+}
+
+fn invalid_data(msg: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{fibonacci::FibonacciCircuit, prover};
+
+    const NAME: &str = "fibonacci";
+
+    // Writes the three artifacts of a proof of F[9] into a fresh directory
+    fn artifacts() -> (tempfile::TempDir, Vec<Fp>) {
+        let dir = tempfile::tempdir().unwrap();
+        let circuit = FibonacciCircuit::<Fp>::new(9);
+        let k = circuit.k();
+        let public_input = circuit.public_input(Fp::one(), Fp::one());
+
+        let params = Params::new(k);
+        let pk = prover::keygen(&params, &circuit).unwrap();
+        let proof = prover::prove(&params, &pk, FibonacciCircuit::new(9), &[&public_input]).unwrap();
+
+        write_params(dir.path().join("params.bin"), NAME, k, &params, pk.get_vk()).unwrap();
+        write_vk(dir.path().join("vk.bin"), NAME, k, pk.get_vk()).unwrap();
+        write_proof(dir.path().join("proof.bin"), NAME, k, pk.get_vk(), &[&public_input], &proof).unwrap();
+        (dir, public_input)
+    }
+
+    #[test]
+    fn artifacts_round_trip() {
+        let (dir, public_input) = artifacts();
+        let (params_header, params) = read_params(dir.path().join("params.bin")).unwrap();
+        let (vk_header, vk) = read_vk(dir.path().join("vk.bin"), &params, &FibonacciCircuit::<Fp>::new(9)).unwrap();
+        let proof = read_proof(dir.path().join("proof.bin")).unwrap();
+
+        assert_eq!(params_header.kind, ArtifactKind::Params);
+        assert_eq!(proof.header.kind, ArtifactKind::Proof);
+        for header in [&params_header, &proof.header] {
+            assert_eq!((header.circuit.as_str(), header.k, header.vk_digest), (NAME, vk_header.k, vk_header.vk_digest));
+        }
+        assert_eq!(proof.instances, vec![public_input]);
+        prover::verify(&params, &vk, &proof.instance_slices(), &proof.proof).unwrap();
+    }
+
+    #[test]
+    fn artifacts_reject_bad_magic_and_kind() {
+        let (dir, _) = artifacts();
+        let path = dir.path().join("proof.bin");
+        let mut bytes = std::fs::read(&path).unwrap();
+
+        // a proof is not a params file
+        let err = read_params(&path).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+
+        bytes[0] = b'X';
+        std::fs::write(&path, &bytes).unwrap();
+        let err = read_proof(&path).unwrap_err();
+        assert_eq!((err.kind(), err.to_string()), (io::ErrorKind::InvalidData, "not a halo2 artifact".to_string()));
+    }
+
+    #[test]
+    fn artifacts_reject_truncated_files() {
+        let (dir, _) = artifacts();
+        let path = dir.path().join("proof.bin");
+        let bytes = std::fs::read(&path).unwrap();
+
+        // every prefix, from the middle of the header to the last byte of the transcript
+        for len in [0, 3, 10, bytes.len() / 2, bytes.len() - 1] {
+            std::fs::write(&path, &bytes[..len]).unwrap();
+            assert_eq!(read_proof(&path).unwrap_err().kind(), io::ErrorKind::UnexpectedEof, "{} bytes", len);
+        }
+
+        // forged lengths are only trusted as far as the file goes. With u32::MAX instance columns
+        // the rest of the file is read as columns until it runs out or doesn't parse
+        let header_len = 4 + 2 + 2 + NAME.len() + 4 + 32;
+        let mut forged = bytes.clone();
+        forged[header_len..header_len + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write(&path, &forged).unwrap();
+        let kind = read_proof(&path).unwrap_err().kind();
+        assert!(matches!(kind, io::ErrorKind::UnexpectedEof | io::ErrorKind::InvalidData), "{:?}", kind);
+
+        // a transcript length past the end of the file
+        let mut forged = bytes.clone();
+        let transcript_len = bytes.len() - header_len - 4 - 4 - 3 * 32 - 4;
+        let at = bytes.len() - transcript_len - 4;
+        assert_eq!(u32::from_le_bytes(forged[at..at + 4].try_into().unwrap()) as usize, transcript_len);
+        forged[at..at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write(&path, &forged).unwrap();
+        assert_eq!(read_proof(&path).unwrap_err().kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn artifacts_reject_another_circuit() {
+        let (dir, _) = artifacts();
+        let (_, params) = read_params(dir.path().join("params.bin")).unwrap();
+
+        // the verifying key of F[8] is not the one of F[9], with the same params
+        assert_eq!(FibonacciCircuit::<Fp>::new(8).k(), FibonacciCircuit::<Fp>::new(9).k());
+        let err = read_vk(dir.path().join("vk.bin"), &params, &FibonacciCircuit::<Fp>::new(8)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("does not match the verifying key"));
+    }
+
+    #[test]
+    fn artifacts_reject_params_for_another_k() {
+        let (dir, _) = artifacts();
+        let k = FibonacciCircuit::<Fp>::new(9).k();
+        let err = read_vk(dir.path().join("vk.bin"), &Params::new(k + 1), &FibonacciCircuit::<Fp>::new(9)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        let mismatch = err.get_ref().and_then(|e| e.downcast_ref::<KMismatch>());
+        assert_eq!(mismatch, Some(&KMismatch { artifact: k, params: k + 1 }));
+    }
+}
@@ -0,0 +1,41 @@
+// Prints the cost report of the example circuits.
+// `cargo run -p chips --bin cost` reports all of them, `cargo run -p chips --bin cost -- range-check`
+// only the ones named.
+use chips::{
+    cost::{cost_report, CostReport, UnknownOutput},
+    examples::{self, Visitor},
+};
+use halo2_proofs::{pasta::Fp, plonk::Circuit};
+
+struct Report;
+
+impl Visitor for Report {
+    type Output = Result<CostReport, UnknownOutput>;
+
+    fn visit<C: Circuit<Fp>>(self, name: &str, k: u32, circuit: C, _: Vec<Vec<Fp>>) -> Self::Output {
+        cost_report(name, k, &circuit)
+    }
+}
+
+fn main() {
+    let names: Vec<String> = std::env::args().skip(1).collect();
+    let names: Vec<&str> = if names.is_empty() {
+        examples::NAMES.to_vec()
+    } else {
+        names.iter().map(String::as_str).collect()
+    };
+
+    for name in names {
+        match examples::visit(name, Report) {
+            Some(Ok(report)) => println!("{}\n", report),
+            Some(Err(e)) => {
+                eprintln!("can't report {}: {}", name, e);
+                std::process::exit(1);
+            }
+            None => {
+                eprintln!("unknown circuit {}, pick one of: {}", name, examples::NAMES.join(", "));
+                std::process::exit(1);
+            }
+        }
+    }
+}
@@ -0,0 +1,46 @@
+// Renders the layout of an example circuit as a PNG or SVG, or its gates as a dot graph.
+// `cargo run -p chips --features dev-graph --bin layout -- fibonacci-folded folded.svg` uses
+// the k of the example, a third argument overrides it.
+use std::path::PathBuf;
+
+use chips::{
+    examples::{self, Visitor},
+    render::render,
+};
+use halo2_proofs::{pasta::Fp, plonk::Circuit};
+
+struct Render {
+    path: PathBuf,
+    k: Option<u32>,
+}
+
+impl Visitor for Render {
+    type Output = Result<(), Box<dyn std::error::Error>>;
+
+    fn visit<C: Circuit<Fp>>(self, name: &str, k: u32, circuit: C, _: Vec<Vec<Fp>>) -> Self::Output {
+        render(name, self.k.unwrap_or(k), &circuit, &self.path)
+    }
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 2 {
+        eprintln!("usage: layout <circuit> <file.png|file.svg|file.dot> [k]");
+        eprintln!("circuits: {}", examples::NAMES.join(", "));
+        std::process::exit(1);
+    }
+    let k = args.get(2).map(|k| k.parse().expect("k must be a number"));
+    let visitor = Render { path: PathBuf::from(&args[1]), k };
+
+    match examples::visit(&args[0], visitor) {
+        Some(Ok(())) => println!("wrote {}", args[1]),
+        Some(Err(e)) => {
+            eprintln!("can't render {}: {}", args[0], e);
+            std::process::exit(1);
+        }
+        None => {
+            eprintln!("unknown circuit {}, pick one of: {}", args[0], examples::NAMES.join(", "));
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,342 @@
+// What a circuit costs: its columns, gates and lookups, how many of the 2^k rows it fills and
+// how big its proof is. halo2 estimates the proof size with CircuitCost and lists the gates with
+// CircuitGates, but most of the numbers are private and only show up in Debug or Display
+// output, so we read them from there and count the rows used ourselves. That output is the one of
+// the pinned halo2_proofs 0.2.0, another version can lay it out differently: reading it then fails
+// with UnknownOutput instead of returning wrong counts.
+use std::{collections::BTreeSet, error, fmt};
+
+use halo2_proofs::{
+    circuit::Value,
+    dev::{CircuitCost, CircuitGates},
+    pasta::{Eq, Fp},
+    plonk::{
+        Advice, Any, Assigned, Assignment, Circuit, Column, ConstraintSystem, Error, Fixed, FloorPlanner,
+        Instance, Selector,
+    },
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostReport {
+    pub name: String,
+    pub k: u32,
+    // rows the circuit can use, the last ones of the 2^k are reserved for the blinding factors
+    pub usable_rows: usize,
+    pub rows_used: usize,
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub selectors: usize,
+    pub lookups: usize,
+    pub gates: usize,
+    pub constraints: usize,
+    pub max_degree: usize,
+    pub permutation_columns: usize,
+    // for one instance of the circuit, in bytes. CircuitCost can't estimate it for a circuit
+    // without any equality constraint, it underflows on the empty permutation argument
+    pub proof_size: Option<usize>,
+}
+
+impl fmt::Display for CostReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "circuit:             {}", self.name)?;
+        writeln!(f, "k:                   {}", self.k)?;
+        writeln!(f, "rows used:           {} of {} usable ({} total)", self.rows_used, self.usable_rows, 1 << self.k)?;
+        writeln!(f, "advice columns:      {}", self.advice_columns)?;
+        writeln!(f, "fixed columns:       {}", self.fixed_columns)?;
+        writeln!(f, "instance columns:    {}", self.instance_columns)?;
+        writeln!(f, "selectors:           {}", self.selectors)?;
+        writeln!(f, "lookups:             {}", self.lookups)?;
+        writeln!(f, "gates:               {} ({} constraints)", self.gates, self.constraints)?;
+        writeln!(f, "max degree:          {}", self.max_degree)?;
+        writeln!(f, "permutation columns: {}", self.permutation_columns)?;
+        match self.proof_size {
+            Some(size) => write!(f, "estimated proof:     {} bytes", size),
+            None => write!(f, "estimated proof:     unknown, no permutation argument"),
+        }
+    }
+}
+
+// The Debug or Display output of halo2 has no `label` where we read a count from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownOutput {
+    pub label: &'static str,
+}
+
+impl fmt::Display for UnknownOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "no {:?} in the halo2 output, is halo2_proofs still 0.2.0?", self.label)
+    }
+}
+
+impl error::Error for UnknownOutput {}
+
+// The counts of a constraint system. They aren't public either, but its Debug shows them
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Shape {
+    advice_columns: usize,
+    fixed_columns: usize,
+    instance_columns: usize,
+    selectors: usize,
+    lookups: usize,
+    permutation_columns: usize,
+}
+
+impl Shape {
+    // Reads the Debug of ConstraintSystem in halo2_proofs 0.2.0: its fields in declaration order,
+    // the permutation as `Argument { columns: [Column { .. }, ..] }` and every lookup as
+    // `Argument { input_expressions: [..], table_expressions: [..] }`
+    fn read(cs: &ConstraintSystem<Fp>) -> Result<Self, UnknownOutput> {
+        let shape = format!("{:?}", cs);
+        let permutation = between(&shape, "permutation: Argument { columns: ", "lookups: ")?;
+        let lookups = between(&shape, "lookups: ", "constants: ")?;
+        Ok(Self {
+            advice_columns: field(&shape, "num_advice_columns: ")?,
+            fixed_columns: field(&shape, "num_fixed_columns: ")?,
+            instance_columns: field(&shape, "num_instance_columns: ")?,
+            selectors: field(&shape, "num_selectors: ")?,
+            lookups: lookups.matches("Argument { input_expressions: ").count(),
+            permutation_columns: permutation.matches("Column {").count(),
+        })
+    }
+}
+
+// Measures `circuit` at size `k`. Panics if the circuit doesn't fit in 2^k rows, like CircuitCost.
+pub fn cost_report<C: Circuit<Fp>>(name: &str, k: u32, circuit: &C) -> Result<CostReport, UnknownOutput> {
+    let mut cs = ConstraintSystem::<Fp>::default();
+    let config = C::configure(&mut cs);
+    let max_degree = cs.degree();
+    let usable_rows = (1 << k) - (cs.blinding_factors() + 1);
+    let shape = Shape::read(&cs)?;
+
+    let mut counter = RowCounter::default();
+    // We can't get the constants of the circuit, they are only used to fill the constant columns
+    // once every region is laid out, so running out of columns for them is expected here
+    match C::FloorPlanner::synthesize(&mut counter, circuit, config, vec![]) {
+        Ok(()) | Err(Error::NotEnoughColumnsForConstants) => (),
+        Err(e) => panic!("{} can't be synthesized: {:?}", name, e),
+    }
+
+    let proof_size = (shape.permutation_columns > 0)
+        .then(|| usize::from(CircuitCost::<Eq, C>::measure(k as usize, circuit).proof_size(1)));
+
+    let gates = CircuitGates::collect::<Fp, C>().to_string();
+
+    Ok(CostReport {
+        name: name.to_string(),
+        k,
+        usable_rows,
+        rows_used: counter.rows.len(),
+        advice_columns: shape.advice_columns,
+        fixed_columns: shape.fixed_columns,
+        instance_columns: shape.instance_columns,
+        selectors: shape.selectors,
+        lookups: shape.lookups,
+        gates: field(&gates, "Total gates: ")?,
+        constraints: field(&gates, "Total custom constraint polynomials: ")?,
+        max_degree,
+        permutation_columns: shape.permutation_columns,
+        proof_size,
+    })
+}
+
+// The number right after `label` in the Debug or Display output of halo2
+fn field(output: &str, label: &'static str) -> Result<usize, UnknownOutput> {
+    let start = output.find(label).ok_or(UnknownOutput { label })? + label.len();
+    let digits: String = output[start..].chars().take_while(char::is_ascii_digit).collect();
+    digits.parse().map_err(|_| UnknownOutput { label })
+}
+
+// The output from `from` up to `to`, which has to come after it
+fn between<'a>(output: &'a str, from: &'static str, to: &'static str) -> Result<&'a str, UnknownOutput> {
+    let start = output.find(from).ok_or(UnknownOutput { label: from })?;
+    let end = output[start..].find(to).ok_or(UnknownOutput { label: to })?;
+    Ok(&output[start..start + end])
+}
+
+// Lays out a circuit and only remembers which rows something was assigned in
+#[derive(Default)]
+struct RowCounter {
+    rows: BTreeSet<usize>,
+}
+
+impl Assignment<Fp> for RowCounter {
+    fn enter_region<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn exit_region(&mut self) {}
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, row: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.rows.insert(row);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<Fp>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn assign_advice<V, VR, A, AR>(&mut self, _: A, _: Column<Advice>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fp>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.rows.insert(row);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(&mut self, _: A, _: Column<Fixed>, row: usize, _: V) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<Fp>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.rows.insert(row);
+        Ok(())
+    }
+
+    fn copy(&mut self, _: Column<Any>, left_row: usize, _: Column<Any>, right_row: usize) -> Result<(), Error> {
+        // a copy to the instance column points at a public input row, which also has to fit
+        self.rows.insert(left_row);
+        self.rows.insert(right_row);
+        Ok(())
+    }
+
+    // Only pads a lookup table up to the usable rows, it doesn't use them
+    fn fill_from_row(&mut self, _: Column<Fixed>, _: usize, _: Value<Assigned<Fp>>) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, _: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        examples::{self, Visitor},
+        fibonacci::{FibonacciCircuit, LayoutCircuit, layout::{Folded, Tall, Wide}},
+        prover,
+    };
+    use halo2_proofs::poly::Rotation;
+
+    struct Measure;
+
+    impl Visitor for Measure {
+        type Output = (CostReport, usize);
+
+        // the report and the size of a real proof
+        fn visit<C: Circuit<Fp>>(self, name: &str, k: u32, circuit: C, instances: Vec<Vec<Fp>>) -> Self::Output {
+            let report = cost_report(name, k, &circuit).unwrap();
+            let instances: Vec<&[Fp]> = instances.iter().map(|i| i.as_slice()).collect();
+            let proof = prover::prove_and_verify(k, circuit, &instances).unwrap();
+            (report, proof.proof_size)
+        }
+    }
+
+    #[test]
+    fn single_column_fibonacci_cost() {
+        let circuit = FibonacciCircuit::<Fp>::new(9);
+        let report = cost_report("fibonacci", circuit.k(), &circuit).unwrap();
+
+        assert_eq!(report.k, 4);
+        assert_eq!(report.rows_used, circuit.rows());
+        assert!(report.rows_used <= report.usable_rows);
+        assert_eq!((report.advice_columns, report.fixed_columns, report.instance_columns), (1, 2, 1));
+        assert_eq!((report.selectors, report.lookups), (1, 0));
+        assert_eq!((report.gates, report.constraints), (1, 1));
+        assert_eq!(report.permutation_columns, 2);
+    }
+
+    #[test]
+    fn constraint_system_debug_is_the_pinned_one() {
+        // A constraint system with a different count of everything. If a halo2_proofs update
+        // changes its Debug output, this fails instead of the reports going wrong
+        let mut cs = ConstraintSystem::<Fp>::default();
+        let advice = [(); 3].map(|_| cs.advice_column());
+        let fixed = cs.fixed_column();
+        let instance = cs.instance_column();
+        let _ = [(); 4].map(|_| cs.selector());
+        let q_lookup = cs.complex_selector();
+        let table = cs.lookup_table_column();
+        for column in advice.iter().take(2) {
+            cs.lookup(|meta| {
+                let q_lookup = meta.query_selector(q_lookup);
+                vec![(q_lookup * meta.query_advice(*column, Rotation::cur()), table)]
+            });
+        }
+        cs.enable_equality(advice[0]);
+        cs.enable_equality(fixed);
+        cs.enable_equality(instance);
+
+        // the table column is a fixed column too
+        assert_eq!(
+            Shape::read(&cs),
+            Ok(Shape {
+                advice_columns: 3,
+                fixed_columns: 2,
+                instance_columns: 1,
+                selectors: 5,
+                lookups: 2,
+                permutation_columns: 3,
+            })
+        );
+    }
+
+    #[test]
+    fn unknown_output_is_an_error() {
+        assert_eq!(field("num_selectors: 4", "num_selectors: "), Ok(4));
+        assert_eq!(field("selectors: 4", "num_selectors: "), Err(UnknownOutput { label: "num_selectors: " }));
+        assert_eq!(field("num_selectors: [4]", "num_selectors: "), Err(UnknownOutput { label: "num_selectors: " }));
+        assert_eq!(between("lookups: [] constants: []", "lookups: ", "constants: "), Ok("lookups: [] "));
+        assert_eq!(between("constants: [] lookups: []", "lookups: ", "constants: "), Err(UnknownOutput { label: "constants: " }));
+    }
+
+    #[test]
+    fn layouts_trade_columns_for_rows() {
+        let wide = cost_report("wide", 8, &LayoutCircuit::<Fp, Wide>::new(50)).unwrap();
+        let tall = cost_report("tall", 8, &LayoutCircuit::<Fp, Tall>::new(50)).unwrap();
+        let folded = cost_report("folded", 8, &LayoutCircuit::<Fp, Folded<4>>::new(50)).unwrap();
+
+        assert!(tall.advice_columns < wide.advice_columns && wide.advice_columns < folded.advice_columns);
+        assert!(tall.rows_used > wide.rows_used && wide.rows_used > folded.rows_used);
+    }
+
+    #[test]
+    fn estimated_proof_size_matches_real_proof() {
+        for name in examples::NAMES {
+            let (report, proof_size) = examples::visit(name, Measure).unwrap();
+            if report.permutation_columns > 0 {
+                // CircuitCost always counts the {-1, 0} point set of a lookup argument, so
+                // without lookups nor previous row queries it is one 32 bytes scalar too many
+                let estimate = report.proof_size.unwrap();
+                assert!(estimate == proof_size || estimate == proof_size + 32, "{}: {} {}", name, estimate, proof_size);
+            } else {
+                assert_eq!(report.proof_size, None, "{}", name);
+            }
+            assert!(report.rows_used <= report.usable_rows, "{}", name);
+        }
+
+        let (report, _) = examples::visit("range-check", Measure).unwrap();
+        assert_eq!((report.lookups, report.fixed_columns), (1, 1));
+        // the 256 rows of the lookup table
+        assert_eq!(report.rows_used, 256);
+    }
+}
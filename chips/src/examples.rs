@@ -0,0 +1,127 @@
+// Every example circuit of the repo under a name, with a witness, its k and its public inputs, so
+// that tools can work on any of them: the cost report, the layout renderer, the benchmarks.
+// Circuit isn't object safe, so instead of a list of circuits the registry hands the one asked
+// for to a Visitor, which is generic over the circuit type.
+use halo2_proofs::{circuit::Value, pasta::Fp, plonk::Circuit};
+
+use crate::{
+    fibonacci::{
+        fibonacci,
+        layout::{Folded, Tall, Wide},
+        BatchFibonacciCircuit, CountdownCircuit, DoublingCircuit, FiboCircuit, FibonacciCircuit, LayoutCircuit,
+        U64FibonacciCircuit,
+    },
+    is_zero::function::FunctionCircuit,
+    range_check::circuit::RangeCheckCircuit,
+};
+
+pub trait Visitor {
+    type Output;
+
+    fn visit<C: Circuit<Fp>>(self, name: &str, k: u32, circuit: C, instances: Vec<Vec<Fp>>) -> Self::Output;
+}
+
+pub const NAMES: &[&str] = &[
+    "fibonacci-three-column",
+    "fibonacci-single-column",
+    "fibonacci-doubling",
+    "fibonacci-batch",
+    "fibonacci-u64",
+    "fibonacci-countdown",
+    "fibonacci-wide",
+    "fibonacci-tall",
+    "fibonacci-folded",
+    "is-zero-function",
+    "range-check",
+];
+
+// Hands the circuit called `name` to the visitor, None for an unknown name
+pub fn visit<V: Visitor>(name: &str, visitor: V) -> Option<V::Output> {
+    let (one, zero) = (Fp::one(), Fp::zero());
+    let output = match name {
+        "fibonacci-three-column" => {
+            let circuit = FiboCircuit::new(Value::known(one), Value::known(one), 9);
+            let instances = vec![circuit.public_input(one, one)];
+            visitor.visit(name, circuit.k(), circuit, instances)
+        }
+        "fibonacci-single-column" => {
+            let circuit = FibonacciCircuit::new(9);
+            let instances = vec![circuit.public_input(one, one)];
+            visitor.visit(name, circuit.k(), circuit, instances)
+        }
+        "fibonacci-doubling" => {
+            let circuit = DoublingCircuit::new(Value::known(1000), 10);
+            let instances = vec![vec![Fp::from(1000), fibonacci(zero, one, 1000)]];
+            visitor.visit(name, circuit.k(), circuit, instances)
+        }
+        "fibonacci-batch" => {
+            let circuit = BatchFibonacciCircuit::<Fp, 2>::new(vec![(one, one, 9), (zero, one, 50), (Fp::from(2), one, 20)]);
+            let instances = vec![circuit.public_input()];
+            visitor.visit(name, circuit.k(), circuit, instances)
+        }
+        "fibonacci-u64" => {
+            let (circuit, public_input) = U64FibonacciCircuit::checked(93, 0, 1).unwrap();
+            let instances = vec![public_input];
+            visitor.visit(name, circuit.k(), circuit, instances)
+        }
+        "fibonacci-countdown" => {
+            let circuit = CountdownCircuit::<Fp, 64>::new(Value::known(9));
+            let instances = vec![CountdownCircuit::<Fp, 64>::public_input(one, one, 9)];
+            visitor.visit(name, circuit.k(), circuit, instances)
+        }
+        "fibonacci-wide" => {
+            let circuit = LayoutCircuit::<Fp, Wide>::new(50);
+            let instances = vec![circuit.public_input(one, one)];
+            visitor.visit(name, circuit.k(), circuit, instances)
+        }
+        "fibonacci-tall" => {
+            let circuit = LayoutCircuit::<Fp, Tall>::new(50);
+            let instances = vec![circuit.public_input(one, one)];
+            visitor.visit(name, circuit.k(), circuit, instances)
+        }
+        "fibonacci-folded" => {
+            let circuit = LayoutCircuit::<Fp, Folded<4>>::new(50);
+            let instances = vec![circuit.public_input(one, one)];
+            visitor.visit(name, circuit.k(), circuit, instances)
+        }
+        "is-zero-function" => {
+            // f(10, 12, 15) = 10 - 12
+            let circuit = FunctionCircuit { a: Fp::from(10), b: Fp::from(12), c: Fp::from(15) };
+            visitor.visit(name, 4, circuit, vec![])
+        }
+        "range-check" => {
+            let circuit = RangeCheckCircuit::<Fp, 8, 256> {
+                value: Value::known(Fp::from(7).into()),
+                large_value: Value::known(Fp::from(255).into()),
+            };
+            visitor.visit(name, 9, circuit, vec![])
+        }
+        _ => return None,
+    };
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::MockProver;
+
+    struct Satisfied;
+
+    impl Visitor for Satisfied {
+        type Output = ();
+
+        fn visit<C: Circuit<Fp>>(self, name: &str, k: u32, circuit: C, instances: Vec<Vec<Fp>>) {
+            let prover = MockProver::run(k, &circuit, instances).unwrap();
+            assert_eq!(prover.verify(), Ok(()), "{} is not satisfied", name);
+        }
+    }
+
+    #[test]
+    fn every_example_is_satisfied() {
+        for name in NAMES {
+            assert!(visit(name, Satisfied).is_some());
+        }
+        assert!(visit("fibonacci", Satisfied).is_none());
+    }
+}
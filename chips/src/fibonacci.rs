@@ -0,0 +1,33 @@
+// The Fibonacci chips of the fibonacci examples.
+//  - three_column:  one row per term, a + b = c on three advice columns (appraoch1_2)
+//  - single_column: one advice column, the gate looks two rows ahead (appraoch2)
+//  - recurrence:    the single column chip for any linear recurrence, Fibonacci is one of them
+//  - doubling:      log(n) rows with the fast doubling identities
+//  - batch:         several single column tables side by side in one circuit
+//  - checked:       the single column table over the integers, every term range checked
+//  - countdown:     a private number of steps up to a fixed maximum
+//  - layout:        one chip for the wide, tall and folded layouts, picked at configure time
+use halo2_proofs::arithmetic::FieldExt;
+
+pub mod batch;
+pub mod checked;
+pub mod countdown;
+pub mod doubling;
+pub mod layout;
+pub mod recurrence;
+pub mod single_column;
+pub mod three_column;
+
+pub use batch::BatchFibonacciCircuit;
+pub use checked::{checked_fibonacci, CheckedFibonacciCircuit, Overflow, U128FibonacciCircuit, U64FibonacciCircuit};
+pub use countdown::{CountdownChip, CountdownCircuit, CountdownConfig};
+pub use doubling::{DoublingChip, DoublingCircuit, DoublingConfig};
+pub use layout::{Layout, LayoutChip, LayoutCircuit, LayoutConfig};
+pub use recurrence::{RecurrenceChip, RecurrenceConfig};
+pub use single_column::{FibonacciChip, FibonacciCircuit, FibonacciConfig};
+pub use three_column::{ACell, FiboChip, FiboCircuit, FiboConfig};
+
+// F[n] computed natively, with F[0] = a and F[1] = b
+pub fn fibonacci<F: FieldExt>(a: F, b: F, n: usize) -> F {
+    recurrence::recurrence([a, b], [F::one(), F::one()], n)
+}
@@ -0,0 +1,184 @@
+// Several Fibonacci sequences in one circuit, so they share the keygen and the proof.
+// Every sequence is its own single column table (see single_column.rs). The tables are spread over
+// LANES copies of the chip, each with its own advice, fixed and selector columns, so the floor
+// planner puts the lanes side by side and the circuit is as tall as the longest lane.
+//
+// lane 0 | lane 1 | ...
+//  a_0   |  a_1   |
+//  b_0   |  b_1   |
+//  ...   |  ...   |
+//  F_0   |  F_1   |
+//  a_2   |        |
+//  ...   |        |
+//
+// All the tables share the instance column, sequence i owns the rows 3i, 3i + 1, 3i + 2:
+// [a_0, b_0, F_0[n_0], a_1, b_1, F_1[n_1], ...]
+use std::marker::PhantomData;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
+
+use super::single_column::{FibonacciChip, FibonacciConfig};
+use crate::prover;
+
+pub struct BatchFibonacciCircuit<F, const LANES: usize> {
+    // (a, b, n) for every sequence, in the order of the instance column
+    pub sequences: Vec<(F, F, usize)>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const LANES: usize> BatchFibonacciCircuit<F, LANES> {
+    pub fn new(sequences: Vec<(F, F, usize)>) -> Self {
+        assert!(LANES >= 1, "the batch needs at least one lane");
+        assert!(!sequences.is_empty(), "the batch needs at least one sequence");
+        for (_, _, n) in sequences.iter() {
+            assert!(*n >= 2, "every table needs at least one addition");
+        }
+        Self { sequences, _marker: PhantomData }
+    }
+
+    // The indices of the sequences each lane holds: every sequence goes to the shortest lane so far
+    pub fn lanes(&self) -> [Vec<usize>; LANES] {
+        let mut lanes: [Vec<usize>; LANES] = std::array::from_fn(|_| Vec::new());
+        let mut heights = [0; LANES];
+        for (i, (_, _, n)) in self.sequences.iter().enumerate() {
+            let lane = (0..LANES).min_by_key(|lane| heights[*lane]).unwrap();
+            lanes[lane].push(i);
+            heights[lane] += n + 1;
+        }
+        lanes
+    }
+
+    //the longest lane, and the instance column needs 3 rows per sequence
+    pub fn rows(&self) -> usize {
+        let longest = self.lanes().iter()
+            .map(|lane| lane.iter().map(|i| self.sequences[*i].2 + 1).sum())
+            .max()
+            .unwrap_or(0);
+        std::cmp::max(longest, 3 * self.sequences.len())
+    }
+
+    //the smallest circuit size that fits all the rows
+    pub fn k(&self) -> u32 {
+        prover::min_k::<F, Self>(self.rows())
+    }
+
+    //[a_0, b_0, F_0[n_0], a_1, b_1, F_1[n_1], ...], the values the instance column must hold
+    pub fn public_input(&self) -> Vec<F> {
+        self.sequences.iter()
+            .flat_map(|(a, b, n)| [*a, *b, super::fibonacci(*a, *b, *n)])
+            .collect()
+    }
+}
+
+impl<F: FieldExt, const LANES: usize> Circuit<F> for BatchFibonacciCircuit<F, LANES> {
+    type Config = [FibonacciConfig; LANES];
+    type FloorPlanner = SimpleFloorPlanner;
+
+    // the seeds are public anyway, the shape of the circuit only depends on the n of every sequence
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.sequences.clone())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance= meta.instance_column();
+        std::array::from_fn(|_| {
+            let advice= meta.advice_column();
+            let coeffs= [meta.fixed_column(), meta.fixed_column()];
+            FibonacciChip::configure(meta, advice, coeffs, instance)
+        })
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        for (lane, sequences) in self.lanes().iter().enumerate() {
+            let chip = FibonacciChip::construct(config[lane].clone());
+
+            for i in sequences {
+                let n = self.sequences[*i].2;
+                let out_cell= chip.assign_at(layouter.namespace(|| format!("sequence {}", i)), [F::one(), F::one()], n + 1, 3 * i)?;
+                chip.expose_public(layouter.namespace(|| format!("out {}", i)), out_cell, 3 * i + 2)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::FibonacciCircuit;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    fn sequences() -> Vec<(Fp, Fp, usize)> {
+        vec![
+            (Fp::from(1), Fp::from(1), 9),
+            (Fp::from(0), Fp::from(1), 50),
+            (Fp::from(2), Fp::from(1), 20),
+            (Fp::from(3), Fp::from(7), 2),
+            (Fp::from(5), Fp::from(8), 30),
+        ]
+    }
+
+    #[test]
+    fn batch_fibonacci() {
+        let circuit = BatchFibonacciCircuit::<Fp, 2>::new(sequences());
+        let public_input = circuit.public_input();
+        assert_eq!(public_input.len(), 15);
+        assert_eq!(&public_input[..3], &[Fp::from(1), Fp::from(1), Fp::from(55)]);
+        assert_eq!(public_input[5], Fp::from(12_586_269_025));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn batch_fibonacci_rejects_a_wrong_sequence() {
+        let circuit = BatchFibonacciCircuit::<Fp, 2>::new(sequences());
+        let k = circuit.k();
+
+        // every output and every seed is checked, not only the first sequence
+        for row in 0..15 {
+            let mut public_input = circuit.public_input();
+            public_input[row] += Fp::one();
+            let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+            assert!(prover.verify().is_err(), "row {} was not checked", row);
+        }
+    }
+
+    #[test]
+    fn batch_fibonacci_lanes_share_the_rows() {
+        // 51 + 31 + 21 + 10 + 3 rows in one lane, the 51 rows of the longest table with three lanes
+        let one_lane = BatchFibonacciCircuit::<Fp, 1>::new(sequences());
+        let three_lanes = BatchFibonacciCircuit::<Fp, 3>::new(sequences());
+        assert_eq!(one_lane.rows(), 116);
+        assert_eq!(three_lanes.rows(), 51);
+        assert!(three_lanes.k() < one_lane.k());
+
+        for lanes in three_lanes.lanes() {
+            assert!(!lanes.is_empty());
+        }
+        MockProver::run(three_lanes.k(), &three_lanes, vec![three_lanes.public_input()]).unwrap().assert_satisfied();
+        MockProver::run(one_lane.k(), &one_lane, vec![one_lane.public_input()]).unwrap().assert_satisfied();
+    }
+
+    #[test]
+    fn batch_fibonacci_real_proof() {
+        let circuit = BatchFibonacciCircuit::<Fp, 2>::new(sequences());
+        let k = circuit.k();
+        let mut public_input = circuit.public_input();
+        let report = prover::prove_and_verify(k, circuit, &[&public_input]).unwrap();
+
+        // one proof for the whole batch, about the size of the proof of a single sequence
+        let single = FibonacciCircuit::<Fp>::new(50);
+        let single_input = single.public_input(Fp::from(0), Fp::from(1));
+        let single_report = prover::prove_and_verify(single.k(), single, &[&single_input]).unwrap();
+        assert!(report.proof_size < 2 * single_report.proof_size);
+
+        public_input[14] += Fp::one();
+        let circuit = BatchFibonacciCircuit::<Fp, 2>::new(sequences());
+        assert!(prover::prove_and_verify(k, circuit, &[&public_input]).is_err());
+    }
+}
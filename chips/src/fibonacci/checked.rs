@@ -0,0 +1,267 @@
+// Fibonacci over the integers instead of the field. The single column table computes the terms
+// modulo p, so for a large n the proven F[n] silently wraps around. Here every term is also
+// decomposed into LIMBS bytes (see range_check/limbs.rs), so the proof asserts that none of them
+// wrapped and that F[n] fits in a u64 (LIMBS = 8) or a u128 (LIMBS = 16).
+//
+// The table is the single column one, in its own region:
+//
+// advice | c_1 | c_2 | selector
+//   a    |  1  |  1  |    1
+//   b    |  1  |  1  |    1
+//   ...  |     |     |
+//  F[n]  |     |     |
+//
+// then every term F[i] is copied into a "F[i] fits" region of one row and decomposed there:
+//
+//  value | l_0 | ... | l_{LIMBS-1} | q_decompose
+//  F[i]  | l_0 | ... | l_{LIMBS-1} |      1
+use std::{error, fmt, marker::PhantomData};
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
+
+use super::{fibonacci, single_column::{FibonacciChip, FibonacciConfig}};
+use crate::{prover, range_check::{limbs::{LimbsConfig, LIMB_BITS}, RangeCheckTable}};
+
+// The first term of the sequence that doesn't fit in `bits` bits
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Overflow {
+    pub term: usize,
+    pub bits: usize,
+}
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "F[{}] doesn't fit in {} bits", self.term, self.bits)
+    }
+}
+
+impl error::Error for Overflow {}
+
+// F[n] computed natively over the integers, failing on the first term larger than `bits` bits
+pub fn checked_fibonacci(a: u128, b: u128, n: usize, bits: usize) -> Result<u128, Overflow> {
+    let max = if bits >= 128 { u128::MAX } else { (1 << bits) - 1 };
+    let check = |term: usize, x: Option<u128>| x.filter(|x| *x <= max).ok_or(Overflow { term, bits });
+
+    let mut a = check(0, Some(a))?;
+    if n == 0 {
+        return Ok(a);
+    }
+    let mut b = check(1, Some(b))?;
+    for term in 2..=n {
+        let next = check(term, a.checked_add(b))?;
+        a = b;
+        b = next;
+    }
+    Ok(b)
+}
+
+#[derive(Debug, Clone)]
+pub struct CheckedFibonacciConfig<F: FieldExt, const LIMBS: usize> {
+    pub fibonacci: FibonacciConfig,
+    pub limbs: LimbsConfig<F, LIMBS>,
+}
+
+// Proves F[0]= a, F[1]= b, ..., F[n] over the integers, with every term smaller than 2^(8 * LIMBS).
+// The instance column is [a, b, F[n]] like for FibonacciCircuit.
+pub struct CheckedFibonacciCircuit<F, const LIMBS: usize> {
+    pub n: usize,
+    _marker: PhantomData<F>,
+}
+
+pub type U64FibonacciCircuit<F> = CheckedFibonacciCircuit<F, 8>;
+pub type U128FibonacciCircuit<F> = CheckedFibonacciCircuit<F, 16>;
+
+impl<F: FieldExt, const LIMBS: usize> CheckedFibonacciCircuit<F, LIMBS> {
+    pub const BITS: usize = LIMB_BITS * LIMBS;
+
+    pub fn new(n: usize) -> Self {
+        assert!(n >= 2, "the table needs at least one addition");
+        Self { n, _marker: PhantomData }
+    }
+
+    // The circuit for F[n] from the seeds a and b with its public input [a, b, F[n]], or the first
+    // term that overflows before synthesis gets to fail on it with Error::Synthesis
+    pub fn checked(n: usize, a: u128, b: u128) -> Result<(Self, Vec<F>), Overflow> {
+        let circuit = Self::new(n);
+        let public_input = circuit.public_input(a, b)?;
+        Ok((circuit, public_input))
+    }
+
+    //one row per term F[0]..F[n], and the 256 rows of the byte table
+    pub fn rows(&self) -> usize {
+        std::cmp::max(self.n + 1, 1 << LIMB_BITS)
+    }
+
+    //the smallest circuit size that fits all the rows
+    pub fn k(&self) -> u32 {
+        prover::min_k::<F, Self>(self.rows())
+    }
+
+    //the first term that doesn't fit for these seeds, synthesis fails on it
+    pub fn first_overflow(&self, a: u128, b: u128) -> Option<Overflow> {
+        checked_fibonacci(a, b, self.n, Self::BITS).err()
+    }
+
+    //[a, b, F[n]], or the first term that overflows: no proof exists for these seeds
+    pub fn public_input(&self, a: u128, b: u128) -> Result<Vec<F>, Overflow> {
+        if let Some(overflow) = self.first_overflow(a, b) {
+            return Err(overflow);
+        }
+        let (a, b) = (F::from_u128(a), F::from_u128(b));
+        Ok(vec![a, b, fibonacci(a, b, self.n)])
+    }
+}
+
+impl<F: FieldExt, const LIMBS: usize> Circuit<F> for CheckedFibonacciCircuit<F, LIMBS> {
+    type Config = CheckedFibonacciConfig<F, LIMBS>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice= meta.advice_column();
+        let coeffs= [meta.fixed_column(), meta.fixed_column()];
+        let instance= meta.instance_column();
+        let fibonacci= FibonacciChip::configure(meta, advice, coeffs, instance);
+
+        let value= meta.advice_column();
+        let limbs= std::array::from_fn(|_| meta.advice_column());
+        let table= RangeCheckTable::configure(meta);
+        let limbs= LimbsConfig::configure(meta, value, limbs, table);
+
+        CheckedFibonacciConfig { fibonacci, limbs }
+    }
+
+    // Fails with Error::Synthesis when a term doesn't fit, checked and first_overflow tell which one
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.limbs.table.load(&mut layouter)?;
+
+        let chip = FibonacciChip::construct(config.fibonacci);
+        let terms= chip.assign_terms(layouter.namespace(||"entire table"), [F::one(), F::one()], self.n + 1, 0)?;
+
+        for (i, term) in terms.iter().enumerate() {
+            config.limbs.assign(layouter.namespace(|| format!("F[{}] fits", i)), term)?;
+        }
+
+        chip.expose_public(layouter.namespace(|| "out"), terms[self.n].clone(), 2)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::fibonacci;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use proptest::prelude::*;
+
+    #[test]
+    fn checked_fibonacci_native() {
+        assert_eq!(checked_fibonacci(1, 1, 9, 64), Ok(55));
+        // F[93] is the last Fibonacci number that fits in a u64, F[186] in a u128
+        assert_eq!(checked_fibonacci(0, 1, 93, 64), Ok(12_200_160_415_121_876_738));
+        assert_eq!(checked_fibonacci(0, 1, 94, 64), Err(Overflow { term: 94, bits: 64 }));
+        assert!(checked_fibonacci(0, 1, 186, 128).is_ok());
+        assert_eq!(checked_fibonacci(0, 1, 187, 128), Err(Overflow { term: 187, bits: 128 }));
+        assert_eq!(checked_fibonacci(1 << 8, 1, 5, 8), Err(Overflow { term: 0, bits: 8 }));
+        assert_eq!(Overflow { term: 94, bits: 64 }.to_string(), "F[94] doesn't fit in 64 bits");
+    }
+
+    #[test]
+    fn u64_fibonacci() {
+        let circuit = U64FibonacciCircuit::<Fp>::new(93);
+        let public_input = circuit.public_input(0, 1).unwrap();
+        assert_eq!(public_input[2], fibonacci(Fp::zero(), Fp::one(), 93));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn checked_constructor_reports_the_overflow() {
+        let (circuit, public_input) = U64FibonacciCircuit::<Fp>::checked(93, 0, 1).unwrap();
+        assert_eq!(public_input, circuit.public_input(0, 1).unwrap());
+        MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap().assert_satisfied();
+
+        assert_eq!(U64FibonacciCircuit::<Fp>::checked(94, 0, 1).err(), Some(Overflow { term: 94, bits: 64 }));
+        assert_eq!(U64FibonacciCircuit::<Fp>::checked(5, u64::MAX as u128 + 1, 1).err(), Some(Overflow { term: 0, bits: 64 }));
+        assert_eq!(U128FibonacciCircuit::<Fp>::checked(187, 0, 1).err(), Some(Overflow { term: 187, bits: 128 }));
+    }
+
+    #[test]
+    fn u64_fibonacci_overflow_is_a_synthesis_error() {
+        let circuit = U64FibonacciCircuit::<Fp>::new(94);
+        assert_eq!(circuit.first_overflow(0, 1), Some(Overflow { term: 94, bits: 64 }));
+        assert_eq!(circuit.public_input(0, 1), Err(Overflow { term: 94, bits: 64 }));
+        assert_eq!(U64FibonacciCircuit::<Fp>::new(93).first_overflow(0, 1), None);
+
+        // the field has no trouble with F[94], the decomposition of the last term does
+        assert!(LimbsConfig::<Fp, 8>::fits(&fibonacci(Fp::zero(), Fp::one(), 93)));
+        assert!(!LimbsConfig::<Fp, 8>::fits(&fibonacci(Fp::zero(), Fp::one(), 94)));
+        let public_input = vec![Fp::zero(), Fp::one(), fibonacci(Fp::zero(), Fp::one(), 94)];
+        assert!(matches!(MockProver::run(circuit.k(), &circuit, vec![public_input]), Err(Error::Synthesis)));
+
+        // same for a seed that is already too large
+        let circuit = U64FibonacciCircuit::<Fp>::new(2);
+        let public_input = vec![-Fp::one(), Fp::one(), Fp::zero()];
+        assert!(matches!(MockProver::run(circuit.k(), &circuit, vec![public_input]), Err(Error::Synthesis)));
+    }
+
+    #[test]
+    fn u128_fibonacci() {
+        let circuit = U128FibonacciCircuit::<Fp>::new(186);
+        let public_input = circuit.public_input(0, 1).unwrap();
+        MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap().assert_satisfied();
+
+        let circuit = U128FibonacciCircuit::<Fp>::new(187);
+        assert_eq!(circuit.public_input(0, 1), Err(Overflow { term: 187, bits: 128 }));
+        let public_input = vec![Fp::zero(), Fp::one(), fibonacci(Fp::zero(), Fp::one(), 187)];
+        assert!(matches!(MockProver::run(circuit.k(), &circuit, vec![public_input]), Err(Error::Synthesis)));
+    }
+
+    #[test]
+    fn u64_fibonacci_real_proof() {
+        let circuit = U64FibonacciCircuit::<Fp>::new(50);
+        let k = circuit.k();
+        let public_input = circuit.public_input(0, 1).unwrap();
+        assert!(prover::prove_and_verify(k, circuit, &[&public_input]).is_ok());
+
+        let wrong_out = [public_input[0], public_input[1], public_input[2] + Fp::one()];
+        assert!(prover::prove_and_verify(k, U64FibonacciCircuit::new(50), &[&wrong_out]).is_err());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        // The circuit proves F[n] exactly when the native checked_fibonacci doesn't overflow a u64.
+        // Small seeds overflow somewhere around n = 90, large ones right away
+        #[test]
+        fn u64_fibonacci_matches_native(
+            a in prop_oneof![0u128..1000, 0u128..1 << 70],
+            b in prop_oneof![0u128..1000, 0u128..1 << 70],
+            n in 2usize..120,
+        ) {
+            let circuit = U64FibonacciCircuit::<Fp>::new(n);
+            let (fa, fb) = (Fp::from_u128(a), Fp::from_u128(b));
+            let public_input = vec![fa, fb, fibonacci(fa, fb, n)];
+            let run = MockProver::run(circuit.k(), &circuit, vec![public_input]);
+
+            match checked_fibonacci(a, b, n, 64) {
+                Ok(out) => {
+                    prop_assert_eq!(circuit.public_input(a, b).unwrap()[2], Fp::from_u128(out));
+                    prop_assert_eq!(run.unwrap().verify(), Ok(()));
+                }
+                Err(overflow) => {
+                    prop_assert_eq!(circuit.public_input(a, b), Err(overflow));
+                    prop_assert!(matches!(run, Err(Error::Synthesis)));
+                }
+            }
+        }
+    }
+}
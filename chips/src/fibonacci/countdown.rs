@@ -0,0 +1,295 @@
+// Fibonacci with a private number of steps. The table always holds the MAX + 1 terms
+// F[0]..F[MAX], and a countdown column starting at the private n picks the result: the IsZeroChip
+// flags the only row where the countdown is 0, that is row n, and a running sum copies out its term.
+// The shape of the circuit only depends on MAX, so one verifying key serves every n <= MAX.
+//
+//  x   | count | count_inv | out        | found    | q_first | q_next | q_fib | q_count
+// -----+-------+-----------+------------+----------+---------+--------+-------+--------
+//  a   |  n    |  1/n      | 0          | 0        |    1    |   0    |   1   |   1
+//  b   |  n-1  |  1/(n-1)  | 0          | 0        |    0    |   1    |   1   |   1
+//  ... |  ...  |           |            |          |         |        |       |
+// F[n] |  0    |  0        | F[n]       | 1        |    0    |   1    |   1   |   1
+//  ... |  ...  |           | F[n]       | 1        |         |        |       |
+// F[M] |  n-M  |           | F[n]       | 1        |    0    |   1    |   0   |   0
+//
+// found sums the zero flags and must end on 1, so there is no proof for an n larger than MAX.
+use std::marker::PhantomData;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+use crate::{is_zero::{IsZeroChip, IsZeroConfig}, prover};
+
+#[derive(Debug, Clone)]
+pub struct CountdownConfig<F: FieldExt> {
+    pub x: Column<Advice>,
+    pub count: Column<Advice>,
+    pub out: Column<Advice>,
+    pub found: Column<Advice>,
+    pub q_first: Selector,
+    pub q_next: Selector,
+    pub q_fib: Selector,
+    pub q_count: Selector,
+    pub count_is_zero: IsZeroConfig<F>,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CountdownChip<F: FieldExt> {
+    config: CountdownConfig<F>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> CountdownChip<F> {
+    pub fn construct(config: CountdownConfig<F>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 5], constant: Column<Fixed>, instance: Column<Instance>) -> CountdownConfig<F> {
+        let [x, count, count_inv, out, found] = advice;
+        // simple selectors can't be added, the IsZeroChip is enabled by q_first + q_next
+        let q_first = meta.complex_selector();
+        let q_next = meta.complex_selector();
+        let q_fib = meta.selector();
+        let q_count = meta.selector();
+
+        meta.enable_equality(x);
+        meta.enable_equality(out);
+        meta.enable_equality(found);
+        meta.enable_equality(instance);
+        // found must end on the constant 1
+        meta.enable_constant(constant);
+
+        // every row of the table is either the first one or a next one
+        let count_is_zero = IsZeroChip::configure(
+            meta,
+            |meta| meta.query_selector(q_first) + meta.query_selector(q_next),
+            |meta| meta.query_advice(count, Rotation::cur()),
+            count_inv,
+        );
+
+        meta.create_gate("add", |meta| {
+            let s = meta.query_selector(q_fib);
+            let x0 = meta.query_advice(x, Rotation::cur());
+            let x1 = meta.query_advice(x, Rotation::next());
+            let x2 = meta.query_advice(x, Rotation(2));
+            vec![s * (x0 + x1 - x2)]
+        });
+
+        meta.create_gate("countdown", |meta| {
+            let s = meta.query_selector(q_count);
+            let cur = meta.query_advice(count, Rotation::cur());
+            let next = meta.query_advice(count, Rotation::next());
+            vec![s * (cur - next - Expression::Constant(F::one()))]
+        });
+
+        // the first row starts the running sums, there is no row above it to query
+        meta.create_gate("select first", |meta| {
+            let q_first = meta.query_selector(q_first);
+            let x = meta.query_advice(x, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            let found = meta.query_advice(found, Rotation::cur());
+            let is_zero = count_is_zero.expr();
+
+            Constraints::with_selector(q_first, [
+                ("out", out - is_zero.clone() * x),
+                ("found", found - is_zero),
+            ])
+        });
+
+        meta.create_gate("select next", |meta| {
+            let q_next = meta.query_selector(q_next);
+            let x = meta.query_advice(x, Rotation::cur());
+            let out_cur = meta.query_advice(out, Rotation::cur());
+            let out_prev = meta.query_advice(out, Rotation::prev());
+            let found_cur = meta.query_advice(found, Rotation::cur());
+            let found_prev = meta.query_advice(found, Rotation::prev());
+            let is_zero = count_is_zero.expr();
+
+            Constraints::with_selector(q_next, [
+                ("out", out_cur - out_prev - is_zero.clone() * x),
+                ("found", found_cur - found_prev - is_zero),
+            ])
+        });
+
+        CountdownConfig {
+            x,
+            count,
+            out,
+            found,
+            q_first,
+            q_next,
+            q_fib,
+            q_count,
+            count_is_zero,
+            instance,
+        }
+    }
+
+    // Fills the table F[0]..F[max] with the seeds from the instance rows 0 and 1 and the countdown
+    // from n. Returns the cell holding F[n].
+    pub fn assign(&self, mut layouter: impl Layouter<F>, n: Value<F>, max: usize) -> Result<AssignedCell<F, F>, Error> {
+        assert!(max >= 1, "the table holds at least the two seeds");
+        let is_zero = IsZeroChip::construct(self.config.count_is_zero.clone());
+
+        layouter.assign_region(
+            || "countdown table",
+            |mut region| {
+                let mut x_cells: Vec<AssignedCell<F, F>> = Vec::with_capacity(max + 1);
+                let mut out_val = Value::known(F::zero());
+                let mut found_val = Value::known(F::zero());
+                let mut out_cell = None;
+                let mut found_cell = None;
+
+                for row in 0..=max {
+                    if row == 0 {
+                        self.config.q_first.enable(&mut region, row)?;
+                    } else {
+                        self.config.q_next.enable(&mut region, row)?;
+                        self.config.q_count.enable(&mut region, row - 1)?;
+                    }
+                    if row >= 2 {
+                        self.config.q_fib.enable(&mut region, row - 2)?;
+                    }
+
+                    let x_cell = if row < 2 {
+                        region.assign_advice_from_instance(|| "seed", self.config.instance, row, self.config.x, row)?
+                    } else {
+                        let x_val = x_cells[row - 2].value().copied() + x_cells[row - 1].value().copied();
+                        region.assign_advice(|| "x", self.config.x, row, || x_val)?
+                    };
+
+                    let count = n - Value::known(F::from(row as u64));
+                    region.assign_advice(|| "count", self.config.count, row, || count)?;
+                    is_zero.assign(&mut region, row, count)?;
+
+                    let flag = count.map(|count| if count == F::zero() { F::one() } else { F::zero() });
+                    out_val = out_val + flag * x_cell.value().copied();
+                    found_val = found_val + flag;
+                    out_cell = Some(region.assign_advice(|| "out", self.config.out, row, || out_val)?);
+                    found_cell = Some(region.assign_advice(|| "found", self.config.found, row, || found_val)?);
+
+                    x_cells.push(x_cell);
+                }
+
+                // exactly one row had a zero countdown
+                region.constrain_constant(found_cell.unwrap().cell(), F::one())?;
+                Ok(out_cell.unwrap())
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+// Proves F[n] for a private n <= MAX, F[0]= a and F[1]= b. The instance column is [a, b, F[n]].
+pub struct CountdownCircuit<F, const MAX: usize> {
+    pub n: Value<u64>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const MAX: usize> CountdownCircuit<F, MAX> {
+    pub fn new(n: Value<u64>) -> Self {
+        Self { n, _marker: PhantomData }
+    }
+
+    //one row per term F[0]..F[MAX], whatever n is
+    pub fn rows(&self) -> usize {
+        MAX + 1
+    }
+
+    //the smallest circuit size that fits all the rows
+    pub fn k(&self) -> u32 {
+        prover::min_k::<F, Self>(self.rows())
+    }
+
+    //[a, b, F[n]], the values the instance column must hold
+    pub fn public_input(a: F, b: F, n: usize) -> Vec<F> {
+        vec![a, b, super::fibonacci(a, b, n)]
+    }
+}
+
+impl<F: FieldExt, const MAX: usize> Circuit<F> for CountdownCircuit<F, MAX> {
+    type Config = CountdownConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(Value::unknown())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [(); 5].map(|_| meta.advice_column());
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        CountdownChip::configure(meta, advice, constant, instance)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = CountdownChip::construct(config);
+        let out_cell = chip.assign(layouter.namespace(|| "countdown table"), self.n.map(F::from), MAX)?;
+        chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    type Countdown64 = CountdownCircuit<Fp, 64>;
+
+    #[test]
+    fn countdown_every_n_up_to_max() {
+        let (a, b) = (Fp::from(1), Fp::from(1));
+        for n in [0u64, 1, 2, 9, 63, 64] {
+            let circuit = Countdown64::new(Value::known(n));
+            let public_input = Countdown64::public_input(a, b, n as usize);
+            MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap().assert_satisfied();
+        }
+        assert_eq!(Countdown64::public_input(a, b, 9)[2], Fp::from(55));
+    }
+
+    #[test]
+    fn countdown_rejects_wrong_output_or_n() {
+        let (a, b) = (Fp::from(1), Fp::from(1));
+        let circuit = Countdown64::new(Value::known(9));
+        let k = circuit.k();
+
+        // F[10] is not the output of n = 9
+        let prover = MockProver::run(k, &circuit, vec![Countdown64::public_input(a, b, 10)]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // the countdown never reaches 0 for n > MAX, and found stays 0
+        let circuit = Countdown64::new(Value::known(65));
+        let prover = MockProver::run(k, &circuit, vec![vec![a, b, Fp::zero()]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn countdown_one_verifying_key_for_every_n() {
+        use halo2_proofs::{pasta::EqAffine, poly::commitment::Params};
+
+        let k = Countdown64::new(Value::unknown()).k();
+        let params: Params<EqAffine> = Params::new(k);
+        let pk = prover::keygen(&params, &Countdown64::new(Value::unknown())).unwrap();
+
+        let (a, b) = (Fp::from(0), Fp::from(1));
+        for n in [5u64, 40] {
+            let public_input = Countdown64::public_input(a, b, n as usize);
+            let proof = prover::prove(&params, &pk, Countdown64::new(Value::known(n)), &[&public_input]).unwrap();
+            assert!(prover::verify(&params, pk.get_vk(), &[&public_input], &proof).is_ok());
+
+            // the same proof says nothing about another output
+            let wrong_input = Countdown64::public_input(a, b, n as usize + 1);
+            assert!(prover::verify(&params, pk.get_vk(), &[&wrong_input], &proof).is_err());
+        }
+    }
+}
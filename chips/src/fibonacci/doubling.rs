@@ -0,0 +1,280 @@
+// Fibonacci in log(n) rows with the fast doubling identities
+//     F(2k)   = F(k) * (2F(k+1) - F(k))
+//     F(2k+1) = F(k)^2 + F(k+1)^2
+// Every row holds a pair (F(k), F(k+1)) and the next row doubles it, adding one when the next bit
+// of n is set. Walking the bits of n from the most significant one, k runs over the prefixes of n
+// and ends on n, so a BITS-bit n needs BITS + 1 rows instead of n + 1 for the FibonacciChip.
+// Here F is the standard sequence F(0) = 0, F(1) = 1.
+//
+// bit  |  acc   |  f      |  g        | selector
+// -----+--------+---------+-----------+---------
+//      |  0     |  0      |  1        |    1
+//  b_0 |  k_1   |  F(k_1) |  F(k_1+1) |    1
+//  b_1 |  k_2   |  F(k_2) |  F(k_2+1) |    1
+//  ... |        |         |           |
+//  b_m |  n     |  F(n)   |  F(n+1)   |
+//
+// acc is the running sum k_{i+1} = 2 k_i + b_i, so the bits are bound to the public n.
+use std::marker::PhantomData;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+use crate::prover;
+
+#[derive(Debug, Clone)]
+pub struct DoublingConfig {
+    pub bit: Column<Advice>,
+    pub acc: Column<Advice>,
+    pub f: Column<Advice>,
+    pub g: Column<Advice>,
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+// the cells holding n and F(n)
+pub type DoublingOutput<F> = (AssignedCell<F, F>, AssignedCell<F, F>);
+
+#[derive(Debug, Clone)]
+pub struct DoublingChip<F: FieldExt> {
+    config: DoublingConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> DoublingChip<F> {
+    pub fn construct(config: DoublingConfig) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 4], constant: Column<Fixed>, instance: Column<Instance>) -> DoublingConfig {
+        let [bit, acc, f, g] = advice;
+        let selector = meta.selector();
+
+        meta.enable_equality(acc);
+        meta.enable_equality(f);
+        meta.enable_equality(g);
+        meta.enable_equality(instance);
+        // the first row (0, 0, 1) is copied from constants
+        meta.enable_constant(constant);
+
+        meta.create_gate("double", |meta| {
+            let s = meta.query_selector(selector);
+            let acc_cur = meta.query_advice(acc, Rotation::cur());
+            let f_cur = meta.query_advice(f, Rotation::cur());
+            let g_cur = meta.query_advice(g, Rotation::cur());
+            let bit_next = meta.query_advice(bit, Rotation::next());
+            let acc_next = meta.query_advice(acc, Rotation::next());
+            let f_next = meta.query_advice(f, Rotation::next());
+            let g_next = meta.query_advice(g, Rotation::next());
+
+            let one = Expression::Constant(F::one());
+            let two = Expression::Constant(F::from(2));
+            // F(2k) and F(2k+1)
+            let double = f_cur.clone() * (two.clone() * g_cur.clone() - f_cur.clone());
+            let double_plus_one = f_cur.clone() * f_cur + g_cur.clone() * g_cur;
+
+            Constraints::with_selector(s, [
+                ("bit is boolean", bit_next.clone() * (one - bit_next.clone())),
+                ("acc_next = 2 acc + bit", two * acc_cur + bit_next.clone() - acc_next),
+                // bit = 0: F(2k),   bit = 1: F(2k+1)
+                ("f_next", double.clone() + bit_next.clone() * (double_plus_one.clone() - double.clone()) - f_next),
+                // bit = 0: F(2k+1), bit = 1: F(2k+2) = F(2k) + F(2k+1)
+                ("g_next", double_plus_one + bit_next * double - g_next),
+            ])
+        });
+
+        DoublingConfig {
+            bit,
+            acc,
+            f,
+            g,
+            selector,
+            instance,
+        }
+    }
+
+    // Walks the `bits` bits of n from the most significant one. Returns the cells holding n and F(n).
+    pub fn assign(&self, mut layouter: impl Layouter<F>, n: Value<u64>, bits: usize) -> Result<DoublingOutput<F>, Error> {
+        layouter.assign_region(
+            || "doubling table",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                let mut acc_cell = region.assign_advice_from_constant(|| "acc", self.config.acc, 0, F::zero())?;
+                let mut f_cell = region.assign_advice_from_constant(|| "F(0)", self.config.f, 0, F::zero())?;
+                region.assign_advice_from_constant(|| "F(1)", self.config.g, 0, F::one())?;
+
+                let mut state = Value::known((0u64, F::zero(), F::one()));
+                for row in 1..=bits {
+                    if row < bits {
+                        self.config.selector.enable(&mut region, row)?;
+                    }
+
+                    let bit = n.map(|n| (n >> (bits - row)) & 1);
+                    state = state.zip(bit).map(|((k, f, g), bit)| {
+                        let double = f * (g + g - f);
+                        let double_plus_one = f.square() + g.square();
+                        if bit == 0 {
+                            (2 * k, double, double_plus_one)
+                        } else {
+                            (2 * k + 1, double_plus_one, double + double_plus_one)
+                        }
+                    });
+
+                    region.assign_advice(|| "bit", self.config.bit, row, || bit.map(F::from))?;
+                    acc_cell = region.assign_advice(|| "acc", self.config.acc, row, || state.map(|(k, _, _)| F::from(k)))?;
+                    f_cell = region.assign_advice(|| "f", self.config.f, row, || state.map(|(_, f, _)| f))?;
+                    region.assign_advice(|| "g", self.config.g, row, || state.map(|(_, _, g)| g))?;
+                }
+
+                Ok((acc_cell, f_cell))
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+// Proves F(n) for any n below 2^bits, the instance column is [n, F(n)].
+// n is a witness, only `bits` is part of the circuit shape.
+pub struct DoublingCircuit<F> {
+    pub n: Value<u64>,
+    pub bits: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> DoublingCircuit<F> {
+    pub fn new(n: Value<u64>, bits: usize) -> Self {
+        assert!((1..=64).contains(&bits), "n is a u64");
+        n.assert_if_known(|n| bits == 64 || n >> bits == 0);
+        Self { n, bits, _marker: PhantomData }
+    }
+
+    //the starting row and one row per bit
+    pub fn rows(&self) -> usize {
+        self.bits + 1
+    }
+
+    pub fn k(&self) -> u32 {
+        prover::min_k::<F, Self>(self.rows())
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for DoublingCircuit<F> {
+    type Config = DoublingConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(Value::unknown(), self.bits)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let constant = meta.fixed_column();
+        let instance = meta.instance_column();
+        DoublingChip::configure(meta, advice, constant, instance)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = DoublingChip::construct(config);
+        let (n_cell, out_cell) = chip.assign(layouter.namespace(|| "doubling table"), self.n, self.bits)?;
+        chip.expose_public(layouter.namespace(|| "n"), n_cell, 0)?;
+        chip.expose_public(layouter.namespace(|| "out"), out_cell, 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::{fibonacci, FibonacciCircuit};
+    use halo2_proofs::{dev::MockProver, pasta::Fp, poly::commitment::Params};
+
+    // F(n) with F(0) = 0, F(1) = 1
+    fn fib(n: u64) -> Fp {
+        fibonacci(Fp::zero(), Fp::one(), n as usize)
+    }
+
+    #[test]
+    fn doubling_matches_native() {
+        let bits = 10;
+        for n in [0u64, 1, 2, 3, 9, 10, 100, 512, 1023] {
+            let circuit = DoublingCircuit::<Fp>::new(Value::known(n), bits);
+            let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(n), fib(n)]]).unwrap();
+            prover.assert_satisfied();
+        }
+        assert_eq!(fib(10), Fp::from(55));
+    }
+
+    #[test]
+    fn doubling_rejects_wrong_output_or_n() {
+        let circuit = DoublingCircuit::<Fp>::new(Value::known(100), 8);
+        let out = fib(100);
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(100), out + Fp::one()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // F(100) is not F(101)
+        let prover = MockProver::run(circuit.k(), &circuit, vec![vec![Fp::from(101), out]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn doubling_rows_grow_with_log_n() {
+        // 2^20 needs a million rows with the FibonacciChip, and 21 here
+        let circuit = DoublingCircuit::<Fp>::new(Value::known(1 << 20), 21);
+        assert_eq!(circuit.rows(), 22);
+        assert_eq!(circuit.k(), 5);
+        assert!(FibonacciCircuit::<Fp>::new(1 << 20).k() >= 20);
+
+        let public_input = vec![Fp::from(1 << 20), fib(1 << 20)];
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn doubling_real_proof() {
+        let circuit = DoublingCircuit::<Fp>::new(Value::known(1000), 10);
+        let k = circuit.k();
+        let out = fib(1000);
+        assert!(prover::prove_and_verify(k, circuit, &[&[Fp::from(1000), out]]).is_ok());
+
+        let circuit = DoublingCircuit::<Fp>::new(Value::known(1000), 10);
+        assert!(prover::prove_and_verify(k, circuit, &[&[Fp::from(1000), out + Fp::one()]]).is_err());
+    }
+
+    #[test]
+    fn doubling_one_key_for_every_n() {
+        // n is a witness, so one proving key covers every n that fits in the bits
+        let params = Params::new(DoublingCircuit::<Fp>::new(Value::unknown(), 10).k());
+        let pk = prover::keygen(&params, &DoublingCircuit::<Fp>::new(Value::unknown(), 10)).unwrap();
+        for n in [0u64, 3, 777, 1023] {
+            let public_input = [Fp::from(n), fib(n)];
+            let proof = prover::prove(&params, &pk, DoublingCircuit::new(Value::known(n), 10), &[&public_input]).unwrap();
+            assert!(prover::verify(&params, pk.get_vk(), &[&public_input], &proof).is_ok(), "n = {}", n);
+        }
+    }
+
+    #[test]
+    fn single_column_matches_doubling() {
+        // both circuits accept the same F(n), and only that one
+        for n in [2u64, 9, 50, 100] {
+            let linear = FibonacciCircuit::<Fp>::new(n as usize);
+            let linear_input = linear.public_input(Fp::zero(), Fp::one());
+            let doubling = DoublingCircuit::<Fp>::new(Value::known(n), 7);
+            let doubling_input = vec![Fp::from(n), linear_input[2]];
+
+            MockProver::run(linear.k(), &linear, vec![linear_input.clone()]).unwrap().assert_satisfied();
+            MockProver::run(doubling.k(), &doubling, vec![doubling_input]).unwrap().assert_satisfied();
+
+            let wrong = vec![Fp::from(n), linear_input[2] + Fp::one()];
+            assert!(MockProver::run(doubling.k(), &doubling, vec![wrong]).unwrap().verify().is_err());
+        }
+    }
+}
@@ -0,0 +1,345 @@
+// One Fibonacci chip for the three ways of laying out the same table, picked at configure time.
+// Whatever the layout, the instance column is [a, b, F[n]], so the circuits are interchangeable
+// and only their cost differs.
+//
+// Wide: one addition per row on three advice columns, the next row copies b and c (three_column.rs)
+//   a   | b   | c
+//   F0  | F1  | F2
+//   F1  | F2  | F3
+//
+// Tall: one advice column, the gate looks two rows ahead (single_column.rs)
+//   x
+//   F0
+//   F1
+//   F2
+//
+// Folded(K): the terms written row after row over K advice columns, no copies. Inside a row the
+// gate checks x_j = x_{j-1} + x_{j-2}, the first two cells of a row use the end of the previous row.
+//   x_0 | x_1 | x_2 | x_3
+//   F0  | F1  | F2  | F3
+//   F4  | F5  | F6  | F7
+use std::marker::PhantomData;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+use super::{
+    single_column::{FibonacciChip, FibonacciConfig},
+    three_column::{FiboChip, FiboConfig},
+};
+use crate::prover;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+    Wide,
+    Tall,
+    Folded(usize),
+}
+
+impl Layout {
+    pub fn advice_columns(&self) -> usize {
+        match self {
+            Layout::Wide => 3,
+            Layout::Tall => 1,
+            Layout::Folded(columns) => *columns,
+        }
+    }
+
+    //the rows of the table proving F[n], the instance column needs 3 rows for [a, b, F[n]]
+    pub fn rows(&self, n: usize) -> usize {
+        assert!(n >= 2, "the table needs at least one addition");
+        let rows = match self {
+            Layout::Wide => n - 1,
+            Layout::Tall => n + 1,
+            Layout::Folded(columns) => (n + 1).div_ceil(*columns),
+        };
+        std::cmp::max(rows, 3)
+    }
+}
+
+// The layout of a circuit is part of its type, since Circuit::configure takes no parameters
+pub trait Strategy {
+    const LAYOUT: Layout;
+}
+
+pub struct Wide;
+pub struct Tall;
+pub struct Folded<const COLUMNS: usize>;
+
+impl Strategy for Wide {
+    const LAYOUT: Layout = Layout::Wide;
+}
+
+impl Strategy for Tall {
+    const LAYOUT: Layout = Layout::Tall;
+}
+
+impl<const COLUMNS: usize> Strategy for Folded<COLUMNS> {
+    const LAYOUT: Layout = Layout::Folded(COLUMNS);
+}
+
+#[derive(Debug, Clone)]
+pub struct FoldedConfig {
+    pub advice: Vec<Column<Advice>>,
+    // the add gate, only with more than two columns
+    pub q_row: Option<Selector>,
+    pub q_carry: Selector,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Debug, Clone)]
+pub enum LayoutConfig {
+    Wide(FiboConfig),
+    Tall(FibonacciConfig),
+    Folded(FoldedConfig),
+}
+
+pub struct LayoutChip<F: FieldExt> {
+    config: LayoutConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> LayoutChip<F> {
+    pub fn construct(config: LayoutConfig) -> Self {
+        Self { config, _marker: PhantomData }
+    }
+
+    // Creates the columns the layout needs, the instance column is shared with the rest of the circuit
+    pub fn configure(meta: &mut ConstraintSystem<F>, layout: Layout, instance: Column<Instance>) -> LayoutConfig {
+        match layout {
+            Layout::Wide => {
+                let advice = [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+                LayoutConfig::Wide(FiboChip::configure(meta, advice, instance))
+            }
+            Layout::Tall => {
+                let advice = meta.advice_column();
+                let coeffs = [meta.fixed_column(), meta.fixed_column()];
+                LayoutConfig::Tall(FibonacciChip::configure(meta, advice, coeffs, instance))
+            }
+            Layout::Folded(columns) => LayoutConfig::Folded(Self::configure_folded(meta, columns, instance)),
+        }
+    }
+
+    fn configure_folded(meta: &mut ConstraintSystem<F>, columns: usize, instance: Column<Instance>) -> FoldedConfig {
+        assert!(columns >= 2, "the seeds go in the first row");
+        let advice: Vec<_> = (0..columns).map(|_| meta.advice_column()).collect();
+        let q_carry = meta.selector();
+
+        for column in advice.iter() {
+            meta.enable_equality(*column);
+        }
+        meta.enable_equality(instance);
+
+        // x_j = x_{j-1} + x_{j-2} inside the row, there is nothing to check with two columns
+        let q_row = (columns > 2).then(|| meta.selector());
+        if let Some(q_row) = q_row {
+            meta.create_gate("add", |meta| {
+                let q = meta.query_selector(q_row);
+                let x: Vec<_> = advice.iter().map(|column| meta.query_advice(*column, Rotation::cur())).collect();
+                Constraints::with_selector(q, (2..columns).map(|j| x[j - 2].clone() + x[j - 1].clone() - x[j].clone()).collect::<Vec<_>>())
+            });
+        }
+
+        // the first two cells of a row continue the previous one
+        meta.create_gate("carry", |meta| {
+            let q = meta.query_selector(q_carry);
+            let prev_last = meta.query_advice(advice[columns - 1], Rotation::prev());
+            let prev_second_last = meta.query_advice(advice[columns - 2], Rotation::prev());
+            let x_0 = meta.query_advice(advice[0], Rotation::cur());
+            let x_1 = meta.query_advice(advice[1], Rotation::cur());
+            Constraints::with_selector(q, [
+                ("x_0", prev_second_last + prev_last.clone() - x_0.clone()),
+                ("x_1", prev_last + x_0 - x_1),
+            ])
+        });
+
+        FoldedConfig { advice, q_row, q_carry, instance }
+    }
+
+    // Copies the seeds from the instance rows 0 and 1 and computes the table up to F[n]. Returns
+    // the cell holding F[n].
+    pub fn assign(&self, mut layouter: impl Layouter<F>, n: usize) -> Result<AssignedCell<F, F>, Error> {
+        assert!(n >= 2, "the table needs at least one addition");
+        match &self.config {
+            LayoutConfig::Wide(config) => {
+                let chip = FiboChip::construct(config.clone());
+                let (_, mut prev_b, mut prev_c) = chip.assign_first_row_from_instance(layouter.namespace(|| "first row"))?;
+                for _i in 3..=n {
+                    let c_cell = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+                    prev_b = prev_c;
+                    prev_c = c_cell;
+                }
+                Ok(prev_c.0)
+            }
+            LayoutConfig::Tall(config) => {
+                let chip = FibonacciChip::construct(config.clone());
+                chip.assign(layouter.namespace(|| "entire table"), [F::one(), F::one()], n + 1)
+            }
+            LayoutConfig::Folded(config) => self.assign_folded(layouter, config, n),
+        }
+    }
+
+    fn assign_folded(&self, mut layouter: impl Layouter<F>, config: &FoldedConfig, n: usize) -> Result<AssignedCell<F, F>, Error> {
+        let columns = config.advice.len();
+        let rows = (n + 1).div_ceil(columns);
+
+        layouter.assign_region(
+            || "folded table",
+            |mut region| {
+                let mut terms: Vec<AssignedCell<F, F>> = Vec::with_capacity(rows * columns);
+                // the last row is filled up, the terms after F[n] are checked like the others
+                for t in 0..rows * columns {
+                    let (row, j) = (t / columns, t % columns);
+                    if j == 0 {
+                        if let Some(q_row) = config.q_row {
+                            q_row.enable(&mut region, row)?;
+                        }
+                        if row > 0 {
+                            config.q_carry.enable(&mut region, row)?;
+                        }
+                    }
+
+                    let cell = if t < 2 {
+                        region.assign_advice_from_instance(|| "seed", config.instance, t, config.advice[j], row)?
+                    } else {
+                        let x_val = terms[t - 2].value().copied() + terms[t - 1].value().copied();
+                        region.assign_advice(|| "x", config.advice[j], row, || x_val)?
+                    };
+                    terms.push(cell);
+                }
+                Ok(terms.swap_remove(n))
+            },
+        )
+    }
+
+    pub fn expose_public(&self, mut layouter: impl Layouter<F>, cell: AssignedCell<F, F>, row: usize) -> Result<(), Error> {
+        let instance = match &self.config {
+            LayoutConfig::Wide(config) => config.instance,
+            LayoutConfig::Tall(config) => config.instance,
+            LayoutConfig::Folded(config) => config.instance,
+        };
+        layouter.constrain_instance(cell.cell(), instance, row)
+    }
+}
+
+// Proves F[n] with F[0]= a and F[1]= b laid out as L. The instance column is [a, b, F[n]].
+pub struct LayoutCircuit<F, L> {
+    pub n: usize,
+    _marker: PhantomData<(F, L)>,
+}
+
+impl<F: FieldExt, L: Strategy> LayoutCircuit<F, L> {
+    pub fn new(n: usize) -> Self {
+        assert!(n >= 2, "the table needs at least one addition");
+        Self { n, _marker: PhantomData }
+    }
+
+    pub fn rows(&self) -> usize {
+        L::LAYOUT.rows(self.n)
+    }
+
+    //the smallest circuit size that fits all the rows
+    pub fn k(&self) -> u32 {
+        prover::min_k::<F, Self>(self.rows())
+    }
+
+    //[a, b, F[n]], the values the instance column must hold
+    pub fn public_input(&self, a: F, b: F) -> Vec<F> {
+        vec![a, b, super::fibonacci(a, b, self.n)]
+    }
+}
+
+impl<F: FieldExt, L: Strategy> Circuit<F> for LayoutCircuit<F, L> {
+    type Config = LayoutConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let instance = meta.instance_column();
+        LayoutChip::configure(meta, L::LAYOUT, instance)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = LayoutChip::construct(config);
+        let out_cell = chip.assign(layouter.namespace(|| "table"), self.n)?;
+        chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::cost_report;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+
+    fn check<L: Strategy>(n: usize) {
+        let circuit = LayoutCircuit::<Fp, L>::new(n);
+        let mut public_input = circuit.public_input(Fp::from(1), Fp::from(1));
+        MockProver::run(circuit.k(), &circuit, vec![public_input.clone()]).unwrap().assert_satisfied();
+
+        public_input[2] += Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err(), "{:?} accepted a wrong output for n = {}", L::LAYOUT, n);
+    }
+
+    #[test]
+    fn every_layout_proves_the_same_statement() {
+        for n in [2, 3, 4, 9, 10, 50] {
+            check::<Wide>(n);
+            check::<Tall>(n);
+            check::<Folded<2>>(n);
+            check::<Folded<3>>(n);
+            check::<Folded<4>>(n);
+        }
+    }
+
+    #[test]
+    fn layout_rows() {
+        assert_eq!(Layout::Wide.rows(50), 49);
+        assert_eq!(Layout::Tall.rows(50), 51);
+        assert_eq!(Layout::Folded(4).rows(50), 13);
+        assert_eq!(Layout::Folded(4).rows(2), 3);
+        assert_eq!(Layout::Folded(8).advice_columns(), 8);
+
+        // fewer rows, smaller k
+        assert!(LayoutCircuit::<Fp, Folded<8>>::new(1000).k() < LayoutCircuit::<Fp, Tall>::new(1000).k());
+    }
+
+    #[test]
+    #[should_panic(expected = "the table needs at least one addition")]
+    fn layout_rows_need_an_addition() {
+        Layout::Wide.rows(0);
+    }
+
+    #[test]
+    fn two_columns_fold_without_an_add_gate() {
+        let two = cost_report("folded2", 8, &LayoutCircuit::<Fp, Folded<2>>::new(50)).unwrap();
+        let four = cost_report("folded4", 8, &LayoutCircuit::<Fp, Folded<4>>::new(50)).unwrap();
+        assert_eq!((two.selectors, two.gates), (1, 1));
+        assert_eq!((four.selectors, four.gates), (2, 2));
+    }
+
+    #[test]
+    fn folded_rejects_tampered_seed() {
+        let circuit = LayoutCircuit::<Fp, Folded<4>>::new(9);
+        let mut public_input = circuit.public_input(Fp::from(1), Fp::from(1));
+        public_input[0] += Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn every_layout_real_proof() {
+        let (a, b, out) = (Fp::from(1), Fp::from(1), Fp::from(55));
+        let wide = LayoutCircuit::<Fp, Wide>::new(9);
+        assert!(prover::prove_and_verify(wide.k(), wide, &[&[a, b, out]]).is_ok());
+        let tall = LayoutCircuit::<Fp, Tall>::new(9);
+        assert!(prover::prove_and_verify(tall.k(), tall, &[&[a, b, out]]).is_ok());
+        let folded = LayoutCircuit::<Fp, Folded<4>>::new(9);
+        assert!(prover::prove_and_verify(folded.k(), folded, &[&[a, b, out]]).is_ok());
+
+        let folded = LayoutCircuit::<Fp, Folded<4>>::new(9);
+        assert!(prover::prove_and_verify(folded.k(), folded, &[&[a, b, out + Fp::one()]]).is_err());
+    }
+}
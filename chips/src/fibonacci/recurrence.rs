@@ -0,0 +1,230 @@
+// The single column chip of appraoch2, generalized to any linear recurrence of order K:
+//     x_n = c_1 * x_{n-1} + c_2 * x_{n-2} + ... + c_K * x_{n-K}
+// The gate looks K rows ahead in the advice column. The weights live in K fixed columns, so they
+// are part of the verifying key and the prover can't change them.
+// Fibonacci is K = 2 with weights [1, 1], Tribonacci K = 3 with [1, 1, 1], Lucas numbers are
+// Fibonacci with the seeds 2 and 1.
+//
+// advice | c_1 | c_2 | selector
+//  x_0   |  1  |  1  |    1
+//  x_1   |  1  |  1  |    1
+//  x_2   |  1  |  1  |    1
+//  ...   |     |     |
+//  x_n   |     |     |
+use std::marker::PhantomData;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Debug, Clone)]
+pub struct RecurrenceConfig<const K: usize> {
+    pub advice: Column<Advice>,
+    pub coeffs: [Column<Fixed>; K],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RecurrenceChip<F: FieldExt, const K: usize> {
+    config: RecurrenceConfig<K>,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt, const K: usize> RecurrenceChip<F, K> {
+    pub fn construct(config: RecurrenceConfig<K>) -> Self {
+        Self {
+            config,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: Column<Advice>, coeffs: [Column<Fixed>; K], instance: Column<Instance>) -> RecurrenceConfig<K> {
+        assert!(K >= 1, "a recurrence needs at least one previous term");
+        let selector = meta.selector();
+
+        meta.enable_equality(advice);
+        meta.enable_equality(instance);
+
+        meta.create_gate("add", |meta| {
+            // x_{i+K} = c_1 * x_{i+K-1} + ... + c_K * x_i, with the selector on row i
+            let s = meta.query_selector(selector);
+            let next = meta.query_advice(advice, Rotation(K as i32));
+            let sum = (0..K).fold(Expression::Constant(F::zero()), |sum, j| {
+                let c = meta.query_fixed(coeffs[j], Rotation::cur());
+                let x = meta.query_advice(advice, Rotation((K - 1 - j) as i32));
+                sum + c * x
+            });
+            vec![s * (sum - next)]
+        });
+
+        RecurrenceConfig {
+            advice,
+            coeffs,
+            selector,
+            instance,
+        }
+    }
+
+    // Copies the K seeds from the instance rows 0..K and fills the column up to row nrows - 1.
+    // `coeffs` is [c_1, ..., c_K]. Returns the last term.
+    pub fn assign(&self, layouter: impl Layouter<F>, coeffs: [F; K], nrows: usize) -> Result<AssignedCell<F, F>, Error> {
+        self.assign_at(layouter, coeffs, nrows, 0)
+    }
+
+    // Same as assign, with the seeds in the instance rows seed_row..seed_row + K, so several tables
+    // can share one instance column
+    pub fn assign_at(&self, layouter: impl Layouter<F>, coeffs: [F; K], nrows: usize, seed_row: usize) -> Result<AssignedCell<F, F>, Error> {
+        Ok(self.assign_terms(layouter, coeffs, nrows, seed_row)?.pop().unwrap())
+    }
+
+    // Same as assign_at, returning every term x_0..x_{nrows-1} instead of the last one
+    pub fn assign_terms(&self, mut layouter: impl Layouter<F>, coeffs: [F; K], nrows: usize, seed_row: usize) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        assert!(nrows > K, "the table needs at least one term after the seeds");
+
+        layouter.assign_region(
+            || "entire recurrence table",
+            |mut region| {
+                let mut terms = Vec::with_capacity(nrows);
+                for row in 0..K {
+                    terms.push(region.assign_advice_from_instance(|| "seed", self.config.instance, seed_row + row, self.config.advice, row)?);
+                }
+
+                for row in K..nrows {
+                    //the gate sitting K rows above checks this cell
+                    let gate_row = row - K;
+                    self.config.selector.enable(&mut region, gate_row)?;
+                    for (j, c) in coeffs.iter().enumerate() {
+                        region.assign_fixed(|| "coefficient", self.config.coeffs[j], gate_row, || Value::known(*c))?;
+                    }
+
+                    // the last K terms, newest first
+                    let x_val = terms.iter().rev().zip(coeffs.iter()).fold(Value::known(F::zero()), |sum, (x, c)| {
+                        sum + x.value().map(|x| *c * *x)
+                    });
+                    let x_cell = region.assign_advice(|| "advice", self.config.advice, row, || x_val)?;
+                    terms.push(x_cell);
+                }
+
+                Ok(terms)
+            },
+        )
+    }
+
+    pub fn expose_public(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cell: AssignedCell<F, F>,
+        row: usize,
+    ) -> Result<(), Error> {
+        layouter.constrain_instance(cell.cell(), self.config.instance, row)
+    }
+}
+
+// x_n computed natively, from the seeds [x_0, ..., x_{K-1}] and the weights [c_1, ..., c_K]
+pub fn recurrence<F: FieldExt, const K: usize>(seeds: [F; K], coeffs: [F; K], n: usize) -> F {
+    if n < K {
+        return seeds[n];
+    }
+    let mut window = seeds;
+    for _ in K..=n {
+        let next = window.iter().rev().zip(coeffs.iter()).fold(F::zero(), |sum, (x, c)| sum + *c * *x);
+        window.rotate_left(1);
+        window[K - 1] = next;
+    }
+    window[K - 1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prover;
+    use halo2_proofs::{dev::MockProver, pasta::{EqAffine, Fp}, poly::commitment::Params};
+
+    // Proves x_n of the recurrence with the given weights, the instance column is [x_0, ..., x_{K-1}, x_n]
+    struct RecurrenceCircuit<F, const K: usize> {
+        coeffs: [F; K],
+        n: usize,
+    }
+
+    impl<F: FieldExt, const K: usize> RecurrenceCircuit<F, K> {
+        fn k(&self) -> u32 {
+            prover::min_k::<F, Self>(self.n + 1)
+        }
+    }
+
+    impl<F: FieldExt, const K: usize> Circuit<F> for RecurrenceCircuit<F, K> {
+        type Config = RecurrenceConfig<K>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        // the weights are part of the circuit, not of the witness
+        fn without_witnesses(&self) -> Self {
+            Self { coeffs: self.coeffs, n: self.n }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            let advice = meta.advice_column();
+            let coeffs = [(); K].map(|_| meta.fixed_column());
+            let instance = meta.instance_column();
+            RecurrenceChip::configure(meta, advice, coeffs, instance)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+            let chip = RecurrenceChip::construct(config);
+            let out_cell = chip.assign(layouter.namespace(|| "entire table"), self.coeffs, self.n + 1)?;
+            chip.expose_public(layouter.namespace(|| "out"), out_cell, K)
+        }
+    }
+
+    fn run<const K: usize>(seeds: [Fp; K], coeffs: [Fp; K], n: usize, out: u64) {
+        assert_eq!(recurrence(seeds, coeffs, n), Fp::from(out));
+
+        let circuit = RecurrenceCircuit { coeffs, n };
+        let mut public_input = seeds.to_vec();
+        public_input.push(Fp::from(out));
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input.clone()]).unwrap();
+        prover.assert_satisfied();
+
+        *public_input.last_mut().unwrap() += Fp::one();
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    #[test]
+    fn fibonacci() {
+        run([Fp::from(1), Fp::from(1)], [Fp::from(1), Fp::from(1)], 9, 55);
+    }
+
+    #[test]
+    fn lucas() {
+        // 2, 1, 3, 4, 7, 11, 18, 29, 47, 76, 123
+        run([Fp::from(2), Fp::from(1)], [Fp::from(1), Fp::from(1)], 10, 123);
+    }
+
+    #[test]
+    fn tribonacci() {
+        // 0, 0, 1, 1, 2, 4, 7, 13, 24, 44, 81
+        run([Fp::from(0), Fp::from(0), Fp::from(1)], [Fp::from(1), Fp::from(1), Fp::from(1)], 10, 81);
+    }
+
+    #[test]
+    fn weighted() {
+        // x_n = 2 x_{n-1} + 3 x_{n-2}: 1, 1, 5, 13, 41, 121
+        run([Fp::from(1), Fp::from(1)], [Fp::from(2), Fp::from(3)], 5, 121);
+    }
+
+    #[test]
+    fn order_one() {
+        // x_n = 3 x_{n-1}: powers of 3
+        run([Fp::from(1)], [Fp::from(3)], 6, 729);
+    }
+
+    #[test]
+    fn weights_are_in_the_verifying_key() {
+        // Same shape, different weights: the fixed columns differ, so the keys do too
+        let params: Params<EqAffine> = Params::new(4);
+        let fibonacci = RecurrenceCircuit { coeffs: [Fp::from(1), Fp::from(1)], n: 9 };
+        let weighted = RecurrenceCircuit { coeffs: [Fp::from(2), Fp::from(3)], n: 9 };
+        let fibonacci_vk = keygen_vk(&params, &fibonacci).unwrap();
+        let weighted_vk = keygen_vk(&params, &weighted).unwrap();
+        assert_ne!(format!("{:?}", fibonacci_vk.pinned()), format!("{:?}", weighted_vk.pinned()));
+    }
+}
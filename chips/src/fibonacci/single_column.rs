@@ -0,0 +1,264 @@
+// Fibonacci in a single advice column, the gate looks two rows ahead.
+// It is the linear recurrence of order 2 with weights [1, 1], see recurrence.rs
+//
+// advice | c_1 | c_2 | selector
+//   a    |  1  |  1  |    1
+//   b    |  1  |  1  |
+//   c    |     |     |
+use std::marker::PhantomData;
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*};
+
+use super::recurrence::{RecurrenceChip, RecurrenceConfig};
+use crate::prover;
+
+pub type FibonacciConfig = RecurrenceConfig<2>;
+pub type FibonacciChip<F> = RecurrenceChip<F, 2>;
+
+// Proves the sequence F[0]= a, F[1]= b, ..., F[n] with F[i]= F[i-2] + F[i-1].
+// The seeds a and b are not witnessed by the circuit, they are copied from the instance column
+// [a, b, F[n]]
+pub struct FibonacciCircuit<F>{
+    pub n: usize,
+    _marker: PhantomData<F>,
+}
+
+impl<F: FieldExt> FibonacciCircuit<F> {
+    pub fn new(n: usize) -> Self {
+        assert!(n >= 2, "the table needs at least one addition");
+        Self { n, _marker: PhantomData }
+    }
+
+    //one row per term F[0]..F[n]
+    pub fn rows(&self) -> usize {
+        self.n + 1
+    }
+
+    //the smallest circuit size that fits all the rows
+    pub fn k(&self) -> u32 {
+        prover::min_k::<F, Self>(self.rows())
+    }
+
+    //[a, b, F[n]], the values the instance column must hold
+    pub fn public_input(&self, a: F, b: F) -> Vec<F> {
+        vec![a, b, super::fibonacci(a, b, self.n)]
+    }
+}
+
+impl<F: FieldExt> Circuit<F> for FibonacciCircuit<F> {
+    type Config = FibonacciConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let advice= meta.advice_column();
+        let coeffs= [meta.fixed_column(), meta.fixed_column()];
+        let instance= meta.instance_column();
+        FibonacciChip::configure(meta, advice, coeffs, instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        let chip = FibonacciChip::construct(config);
+
+        let out_cell= chip.assign(layouter.namespace(||"entire table"), [F::one(), F::one()], self.rows())?;
+
+        chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::fibonacci;
+    use halo2_proofs::{
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+    use proptest::prelude::*;
+
+    // FibonacciCircuit where the prover adds one to the term F[row], so that it and every term
+    // after it are off
+    struct TamperedCircuit {
+        n: usize,
+        row: usize,
+    }
+
+    impl Circuit<Fp> for TamperedCircuit {
+        type Config = FibonacciConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { n: self.n, row: self.row }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FibonacciCircuit::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let out_cell= layouter.assign_region(||"entire recurrence table", |mut region|{
+                let mut x0= region.assign_advice_from_instance(||"seed", config.instance, 0, config.advice, 0)?;
+                let mut x1= region.assign_advice_from_instance(||"seed", config.instance, 1, config.advice, 1)?;
+                for row in 2..=self.n {
+                    config.selector.enable(&mut region, row - 2)?;
+                    for c in config.coeffs {
+                        region.assign_fixed(||"coefficient", c, row - 2, ||Value::known(Fp::one()))?;
+                    }
+                    let tamper= if row == self.row { Fp::one() } else { Fp::zero() };
+                    let x_val= x0.value().copied() + x1.value().copied() + Value::known(tamper);
+                    let x2= region.assign_advice(||"advice", config.advice, row, ||x_val)?;
+                    x0= x1;
+                    x1= x2;
+                }
+                Ok(x1)
+            })?;
+            layouter.constrain_instance(out_cell.cell(), config.instance, 2)
+        }
+    }
+
+    // the last term of the table and the output row of the instance column don't match
+    fn output_permutation(n: usize) -> Vec<VerifyFailure> {
+        vec![
+            VerifyFailure::Permutation {
+                column: (Any::Advice, 0).into(),
+                location: FailureLocation::InRegion { region: (0, "entire recurrence table").into(), offset: n },
+            },
+            VerifyFailure::Permutation {
+                column: (Any::Instance, 0).into(),
+                location: FailureLocation::OutsideRegion { row: 2 },
+            },
+        ]
+    }
+
+    #[test]
+    fn fibonacci_single_column_rejects_tampered_output() {
+        let circuit = FibonacciCircuit::<Fp>::new(9);
+        let mut public_input = circuit.public_input(Fp::from(1), Fp::from(1));
+        public_input[2] += Fp::one();
+
+        let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert_eq!(prover.verify(), Err(output_permutation(9)));
+    }
+
+    #[test]
+    fn fibonacci_single_column_rejects_tampered_seeds() {
+        // the seeds are copied from the instance column, so the prover has to compute with them:
+        // every gate holds and only the output gives the wrong seed away
+        let circuit = FibonacciCircuit::<Fp>::new(9);
+        for row in 0..2 {
+            let mut public_input = circuit.public_input(Fp::from(1), Fp::from(1));
+            public_input[row] += Fp::one();
+            let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+            assert_eq!(prover.verify(), Err(output_permutation(9)));
+        }
+    }
+
+    #[test]
+    fn fibonacci_single_column_rejects_tampered_intermediate() {
+        let circuit = TamperedCircuit { n: 9, row: 5 };
+        let mut public_input = vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+
+        // F[5] = 8 is witnessed as 9, the gate enabled on F[3] is the only one that doesn't hold
+        let add_failure = || VerifyFailure::ConstraintNotSatisfied {
+            constraint: ((0, "add").into(), 0, "").into(),
+            location: FailureLocation::InRegion { region: (0, "entire recurrence table").into(), offset: 3 },
+            cell_values: vec![
+                (((Any::Advice, 0).into(), 0).into(), "0x3".to_string()),
+                (((Any::Advice, 0).into(), 1).into(), "0x5".to_string()),
+                (((Any::Advice, 0).into(), 2).into(), "0x9".to_string()),
+                (((Any::Fixed, 0).into(), 0).into(), "1".to_string()),
+                (((Any::Fixed, 1).into(), 0).into(), "1".to_string()),
+            ],
+        };
+
+        // the error carries on to F[9], which no longer matches the public output
+        let prover = MockProver::run(4, &circuit, vec![public_input.clone()]).unwrap();
+        let mut expected = vec![add_failure()];
+        expected.extend(output_permutation(9));
+        assert_eq!(prover.verify(), Err(expected));
+
+        // with the output the prover computed, 55 + 5, only the gate catches it
+        public_input[2] = Fp::from(60);
+        let prover = MockProver::run(4, &circuit, vec![public_input]).unwrap();
+        assert_eq!(prover.verify(), Err(vec![add_failure()]));
+    }
+
+    #[test]
+    fn fibonacci_single_column() {
+        let a = Fp::from(1); // F[0]
+        let b = Fp::from(1); // F[1]
+        let out = Fp::from(55); // F[9]
+
+        let circuit = FibonacciCircuit::new(9);
+        let k = circuit.k();
+        assert_eq!(k, 4);
+
+        let public_input = circuit.public_input(a, b);
+        assert_eq!(public_input, vec![a, b, out]);
+
+        let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    #[test]
+    fn fibonacci_single_column_k_grows_with_n() {
+        for n in [2, 9, 50, 1000] {
+            let circuit = FibonacciCircuit::new(n);
+            let k = circuit.k();
+            let public_input = circuit.public_input(Fp::from(1), Fp::from(1));
+            MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap().assert_satisfied();
+
+            // one size smaller doesn't have enough rows
+            assert!(MockProver::run(k - 1, &circuit, vec![public_input]).is_err());
+        }
+    }
+
+    #[test]
+    fn fibonacci_native() {
+        assert_eq!(fibonacci(Fp::from(1), Fp::from(1), 9), Fp::from(55));
+        assert_eq!(fibonacci(Fp::from(0), Fp::from(1), 50), Fp::from(12_586_269_025));
+    }
+
+    #[test]
+    fn fibonacci_single_column_real_proof() {
+        let a = Fp::from(1); // F[0]
+        let b = Fp::from(1); // F[1]
+        let out = Fp::from(55); // F[9]
+
+        let k = FibonacciCircuit::<Fp>::new(9).k();
+        let report = prover::prove_and_verify(k, FibonacciCircuit::new(9), &[&[a, b, out]]).unwrap();
+        assert!(report.proof_size > 0);
+
+        // The proof is still created with a wrong output, but it must not verify
+        let wrong_out = out + Fp::one();
+        assert!(prover::prove_and_verify(k, FibonacciCircuit::new(9), &[&[a, b, wrong_out]]).is_err());
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        // Any seeds and length: the circuit accepts the F[n] computed natively, and nothing else
+        // in any of the three instance rows
+        #[test]
+        fn fibonacci_single_column_matches_native(a: u64, b: u64, n in 2usize..100, row in 0usize..3, delta in 1u64..) {
+            let (a, b) = (Fp::from(a), Fp::from(b));
+            let circuit = FibonacciCircuit::new(n);
+            let mut public_input = vec![a, b, fibonacci(a, b, n)];
+
+            let prover = MockProver::run(circuit.k(), &circuit, vec![public_input.clone()]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+
+            public_input[row] += Fp::from(delta);
+            let prover = MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+            prop_assert!(prover.verify().is_err());
+        }
+    }
+}
@@ -0,0 +1,387 @@
+// One row per term on three advice columns: a + b = c, and the next row copies b and c into a and b.
+//Difference: if you want two chips to reuse the same columns, you have to manually specift them
+use std::marker::PhantomData;
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::*,
+    plonk::*, poly::Rotation,
+};
+
+use crate::prover;
+
+#[derive(Debug, Clone)]
+pub struct ACell<F: FieldExt>(pub AssignedCell<F, F>);
+
+type FirstRow<F> = (ACell<F>, ACell<F>, ACell<F>);
+
+#[derive(Debug, Clone)]
+pub struct FiboConfig{
+    pub advice: [Column<Advice>; 3],
+    pub selector: Selector,
+    pub instance: Column<Instance>,
+}
+
+pub struct FiboChip<F: FieldExt>{
+    config: FiboConfig,
+    _marker: PhantomData<F>,
+}
+
+impl<F:FieldExt> FiboChip<F>  {
+    pub fn construct(config: FiboConfig) -> Self {
+        Self { config, _marker: PhantomData}
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 3], instance: Column<Instance>) -> FiboConfig {
+
+        let col_a= advice[0];
+        let col_b= advice[1];
+        let col_c= advice[2];
+        let selector: Selector= meta.selector();
+
+        meta.enable_equality(col_a);
+        meta.enable_equality(col_b);
+        meta.enable_equality(col_c);
+        meta.enable_equality(instance);
+
+
+        meta.create_gate("add", |meta|{
+            //this expression will usually correspond to a cell like a relative cell inside a custom gate
+            let s= meta.query_selector(selector);
+            let a= meta.query_advice(col_a, Rotation::cur());
+            let b= meta.query_advice(col_b, Rotation::cur());
+            let c= meta.query_advice(col_c, Rotation::cur());
+            //Rotation::next(): you query the next row, relative next row for this cell
+            //With Rotation, we can define an offset like 5, 20, -100, etc. It is relative to the row.
+            vec![s*(a + b - c)] // means s * ( a + b - c) == 0
+        });
+        FiboConfig {
+            advice: [col_a, col_b, col_c ],
+            selector,
+            instance,
+        }
+    }
+
+    // Same as configure, but the chip creates its own columns, so no other chip can reuse them
+    pub fn configure_own_columns(meta: &mut ConstraintSystem<F>) -> FiboConfig {
+        let advice= [meta.advice_column(), meta.advice_column(), meta.advice_column()];
+        let instance= meta.instance_column();
+        Self::configure(meta, advice, instance)
+    }
+
+    pub fn assign_first_row(&self, mut layouter: impl Layouter<F>, a: Value<F>, b: Value<F>) -> Result
+    <FirstRow<F>, Error>{
+        layouter.assign_region(||"first row", |mut region|{
+            self.config.selector.enable(&mut region, 0)?;
+
+            let a_cell= region.assign_advice(
+                || "a",
+                self.config.advice[0],
+                0,
+                || a,
+            ).map(ACell)?;
+
+            let b_cell= region.assign_advice(
+                || "b",
+                self.config.advice[1],
+                0,
+                || b,
+            ).map(ACell)?;
+
+            let c_val= a.and_then(|a| b.map(|b| a + b));
+
+            let c_cell= region.assign_advice(
+                || "c",
+                self.config.advice[2],
+                0,
+                || c_val,
+            ).map(ACell)?;
+
+            Ok((a_cell, b_cell, c_cell))
+
+        })
+    }
+
+    // Same as assign_first_row, with a and b copied from the instance rows 0 and 1 instead of witnessed
+    pub fn assign_first_row_from_instance(&self, mut layouter: impl Layouter<F>) -> Result<FirstRow<F>, Error>{
+        layouter.assign_region(||"first row", |mut region|{
+            self.config.selector.enable(&mut region, 0)?;
+
+            let a_cell= region.assign_advice_from_instance(||"a", self.config.instance, 0, self.config.advice[0], 0).map(ACell)?;
+            let b_cell= region.assign_advice_from_instance(||"b", self.config.instance, 1, self.config.advice[1], 0).map(ACell)?;
+
+            let c_val= a_cell.0.value().copied() + b_cell.0.value().copied();
+            let c_cell= region.assign_advice(||"c", self.config.advice[2], 0, ||c_val).map(ACell)?;
+
+            Ok((a_cell, b_cell, c_cell))
+        })
+    }
+
+    pub fn assign_row(&self, mut layouter: impl Layouter<F>, prev_b: &ACell<F>, prev_c: &ACell<F>) -> Result<ACell<F>, Error> {
+        layouter.assign_region(||"next row", |mut region|{
+            self.config.selector.enable(&mut region, 0)?;//enable the selector to turn on the custom gate
+
+            prev_b.0.copy_advice(||"a", &mut region, self.config.advice[0], 0)?;
+            //prev_b.0.copy_advice(||"a", &mut region: current region, self.config.advice[0]: the first advice column inside our config(row), 0: offset like the current row, the first row in the region)?; a description of the description previous line
+            prev_c.0.copy_advice(||"b", &mut region, self.config.advice[1], 0)?;
+
+            let c_val= prev_b.0.value().and_then(|b| {
+                prev_c.0.value().map(|c| *b + *c)
+            });
+
+            let c_cell= region.assign_advice(||"c", self.config.advice[2], 0, ||c_val).map(ACell)?;
+            Ok(c_cell)
+        })
+    }
+
+    //We will take an assigned cell and then constrain to be equal the instance column value
+    pub fn expose_public(&self, mut layouter: impl Layouter<F>, cell: &ACell<F>, row: usize/*an absolute row number inside the instance column*/) -> Result<(), Error>{
+        layouter.constrain_instance(cell.0.cell(), self.config.instance, row)
+    }
+}
+
+// Proves the sequence F[0]= a, F[1]= b, ..., F[n] with F[i]= F[i-2] + F[i-1].
+// The instance column is [a, b, F[n]]
+pub struct FiboCircuit<F>{
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
+}
+
+impl<F:FieldExt> FiboCircuit<F> {
+    pub fn new(a: Value<F>, b: Value<F>, n: usize) -> Self {
+        assert!(n >= 2, "the first row already computes F[2]");
+        Self { a, b, n }
+    }
+
+    //one row per term after the seeds, and the instance column needs 3 rows for [a, b, F[n]]
+    pub fn rows(&self) -> usize {
+        std::cmp::max(self.n - 1, 3)
+    }
+
+    //the smallest circuit size that fits all the rows
+    pub fn k(&self) -> u32 {
+        prover::min_k::<F, Self>(self.rows())
+    }
+
+    //[a, b, F[n]], the values the instance column must hold
+    pub fn public_input(&self, a: F, b: F) -> Vec<F> {
+        vec![a, b, super::fibonacci(a, b, self.n)]
+    }
+}
+
+impl<F:FieldExt> Circuit<F> for FiboCircuit<F> {
+    type Config = FiboConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(Value::unknown(), Value::unknown(), self.n)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let col_a= meta.advice_column();
+        let col_b= meta.advice_column();
+        let col_c= meta.advice_column();
+        let instance= meta.instance_column();
+
+        FiboChip::configure(meta, [col_a, col_b, col_c], instance)
+    }
+
+    fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip= FiboChip::construct(config);
+
+        let (prev_a, mut prev_b, mut prev_c)= chip.assign_first_row(layouter.namespace(||"first row"), self.a, self.b)?;
+
+        chip.expose_public(layouter.namespace(||"private a"), &prev_a, 0)?;
+        chip.expose_public(layouter.namespace(||"private b"), &prev_b, 1)?;
+
+        for _i in 3..=self.n {
+            let c_cell= chip. assign_row(layouter.namespace(||"next row"), &prev_b, &prev_c)?;
+            prev_b= prev_c;
+            prev_c= c_cell;
+        }
+
+        chip.expose_public(layouter.namespace(||"out"), &prev_c, 2)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::fibonacci;
+    use halo2_proofs::{
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+    use proptest::prelude::*;
+
+    // FiboCircuit where the prover adds one to the c cell of a "next row" region, so that F[row]
+    // and every term after it are off
+    struct TamperedCircuit {
+        n: usize,
+        row: usize,
+    }
+
+    impl Circuit<Fp> for TamperedCircuit {
+        type Config = FiboConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { n: self.n, row: self.row }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FiboCircuit::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip= FiboChip::construct(config.clone());
+            let (prev_a, mut prev_b, mut prev_c)= chip.assign_first_row(layouter.namespace(||"first row"), Value::known(Fp::one()), Value::known(Fp::one()))?;
+            chip.expose_public(layouter.namespace(||"private a"), &prev_a, 0)?;
+            chip.expose_public(layouter.namespace(||"private b"), &prev_b, 1)?;
+
+            for i in 3..=self.n {
+                let c_cell= if i == self.row {
+                    layouter.assign_region(||"next row", |mut region|{
+                        config.selector.enable(&mut region, 0)?;
+                        prev_b.0.copy_advice(||"a", &mut region, config.advice[0], 0)?;
+                        prev_c.0.copy_advice(||"b", &mut region, config.advice[1], 0)?;
+                        let c_val= prev_b.0.value().copied() + prev_c.0.value().copied() + Value::known(Fp::one());
+                        region.assign_advice(||"c", config.advice[2], 0, ||c_val).map(ACell)
+                    })?
+                } else {
+                    chip.assign_row(layouter.namespace(||"next row"), &prev_b, &prev_c)?
+                };
+                prev_b= prev_c;
+                prev_c= c_cell;
+            }
+
+            chip.expose_public(layouter.namespace(||"out"), &prev_c, 2)
+        }
+    }
+
+    // the advice cell and the instance cell of a copy constraint that doesn't hold
+    fn permutation(advice: usize, region: (usize, &str), instance_row: usize) -> Vec<VerifyFailure> {
+        vec![
+            VerifyFailure::Permutation {
+                column: (Any::Advice, advice).into(),
+                location: FailureLocation::InRegion { region: region.into(), offset: 0 },
+            },
+            VerifyFailure::Permutation {
+                column: (Any::Instance, 0).into(),
+                location: FailureLocation::OutsideRegion { row: instance_row },
+            },
+        ]
+    }
+
+    #[test]
+    fn fibonacci_three_column_rejects_tampered_output() {
+        let circuit= FiboCircuit::new(Value::known(Fp::from(1)), Value::known(Fp::from(1)), 9);
+        let mut public_input= circuit.public_input(Fp::from(1), Fp::from(1));
+        public_input[2] += Fp::one();
+
+        // F[9] is the c cell of the last "next row" region, number 7 after the first row and F[3]..F[8]
+        let prover= MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert_eq!(prover.verify(), Err(permutation(2, (7, "next row"), 2)));
+    }
+
+    #[test]
+    fn fibonacci_three_column_rejects_tampered_seeds() {
+        let circuit= FiboCircuit::new(Value::known(Fp::from(1)), Value::known(Fp::from(1)), 9);
+
+        // a is only used in the first row
+        let mut public_input= circuit.public_input(Fp::from(1), Fp::from(1));
+        public_input[0] += Fp::one();
+        let prover= MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert_eq!(prover.verify(), Err(permutation(0, (0, "first row"), 0)));
+
+        // b is also copied into the a cell of the second row, the first cell of the cycle is reported
+        let mut public_input= circuit.public_input(Fp::from(1), Fp::from(1));
+        public_input[1] += Fp::one();
+        let prover= MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert_eq!(prover.verify(), Err(permutation(0, (1, "next row"), 1)));
+    }
+
+    #[test]
+    fn fibonacci_three_column_rejects_tampered_intermediate() {
+        // F[5] = 8 is witnessed as 9 in region 3, the only row where a + b = c doesn't hold
+        let circuit= TamperedCircuit { n: 9, row: 5 };
+        let mut public_input= vec![Fp::from(1), Fp::from(1), Fp::from(55)];
+
+        let add_failure= || VerifyFailure::ConstraintNotSatisfied {
+            constraint: ((0, "add").into(), 0, "").into(),
+            location: FailureLocation::InRegion { region: (3, "next row").into(), offset: 0 },
+            cell_values: vec![
+                (((Any::Advice, 0).into(), 0).into(), "0x3".to_string()),
+                (((Any::Advice, 1).into(), 0).into(), "0x5".to_string()),
+                (((Any::Advice, 2).into(), 0).into(), "0x9".to_string()),
+            ],
+        };
+
+        // the error carries on to F[9], which no longer matches the public output
+        let prover= MockProver::run(4, &circuit, vec![public_input.clone()]).unwrap();
+        let mut expected= vec![add_failure()];
+        expected.extend(permutation(2, (7, "next row"), 2));
+        assert_eq!(prover.verify(), Err(expected));
+
+        // with the output the prover computed, 55 + 5, only the gate catches it
+        public_input[2]= Fp::from(60);
+        let prover= MockProver::run(4, &circuit, vec![public_input]).unwrap();
+        assert_eq!(prover.verify(), Err(vec![add_failure()]));
+    }
+
+    #[test]
+    fn fibonacci_three_column_real_proof() {
+        let a= Fp::from(1);
+        let b= Fp::from(1);
+        let out= Fp::from(55);
+
+        let circuit= FiboCircuit::new(Value::known(a), Value::known(b), 9);
+        let k= circuit.k();
+        assert_eq!(k, 4);
+        assert_eq!(circuit.public_input(a, b), vec![a, b, out]);
+
+        let report= prover::prove_and_verify(k, circuit, &[&[a, b, out]]).unwrap();
+        assert!(report.proof_size > 0);
+
+        // The proof is still created with a wrong output, but it must not verify
+        let circuit= FiboCircuit::new(Value::known(a), Value::known(b), 9);
+        let wrong_out= out + Fp::one();
+        assert!(prover::prove_and_verify(k, circuit, &[&[a, b, wrong_out]]).is_err());
+    }
+
+    #[test]
+    fn fibonacci_three_column_k_grows_with_n() {
+        for n in [2, 9, 50, 200] {
+            let circuit= FiboCircuit::new(Value::known(Fp::from(1)), Value::known(Fp::from(1)), n);
+            let k= circuit.k();
+            let public_input= circuit.public_input(Fp::from(1), Fp::from(1));
+            MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap().assert_satisfied();
+
+            // one size smaller doesn't have enough rows
+            assert!(MockProver::run(k - 1, &circuit, vec![public_input]).is_err());
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        // Any seeds and length: the circuit accepts the F[n] computed natively, and nothing else
+        // in any of the three instance rows
+        #[test]
+        fn fibonacci_three_column_matches_native(a: u64, b: u64, n in 2usize..100, row in 0usize..3, delta in 1u64..) {
+            let (a, b)= (Fp::from(a), Fp::from(b));
+            let circuit= FiboCircuit::new(Value::known(a), Value::known(b), n);
+            let mut public_input= vec![a, b, fibonacci(a, b, n)];
+
+            let prover= MockProver::run(circuit.k(), &circuit, vec![public_input.clone()]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+
+            public_input[row] += Fp::from(delta);
+            let prover= MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+            prop_assert!(prover.verify().is_err());
+        }
+    }
+}
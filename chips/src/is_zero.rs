@@ -0,0 +1,220 @@
+// value == 0 as the expression 1 - value * value_inv, and IsZeroCellChip also witnesses it in
+// a cell that can be copied to other regions or exposed publicly
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+pub mod compare;
+pub mod function;
+pub use compare::{AssertNonZeroChip, AssertNonZeroConfig, CellInputs, IsEqualChip, IsEqualConfig, IsNotEqualChip, IsNotEqualConfig};
+
+#[derive(Clone, Debug)]
+pub struct IsZeroConfig<F> {
+    pub value_inv: Column<Advice>,
+    pub is_zero_expr: Expression<F>,
+}
+
+impl<F: FieldExt> IsZeroConfig<F> {
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero_expr.clone()
+    }
+}
+
+pub struct IsZeroChip<F: FieldExt> {
+    config: IsZeroConfig<F>,
+}
+
+impl<F: FieldExt> IsZeroChip<F> {
+    pub fn construct(config: IsZeroConfig<F>) -> Self {
+        IsZeroChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+    ) -> IsZeroConfig<F> {
+        let mut is_zero_expr = Expression::Constant(F::zero());
+
+        meta.create_gate("is_zero", |meta| {
+            //
+            // valid | value |  value_inv |  1 - value * value_inv | value * (1 - value* value_inv)
+            // ------+-------+------------+------------------------+-------------------------------
+            //  yes  |   x   |    1/x     |         0              |  0
+            //  no   |   x   |    0       |         1              |  x
+            //  yes  |   0   |    0       |         1              |  0
+            //  yes  |   0   |    y       |         1              |  0
+            //
+            let value = value(meta);
+            let q_enable = q_enable(meta);
+            let value_inv = meta.query_advice(value_inv, Rotation::cur());
+
+            is_zero_expr = Expression::Constant(F::one()) - value.clone() * value_inv;
+            vec![q_enable * value * is_zero_expr.clone()]
+        });
+
+        IsZeroConfig {
+            value_inv,
+            is_zero_expr,
+        }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<(), Error> {
+        let value_inv = value.map(|value| value.invert().unwrap_or(F::zero()));
+        region.assign_advice(|| "value inv", self.config.value_inv, offset, || value_inv)?;
+        Ok(())
+    }
+}
+#[derive(Clone, Debug)]
+pub struct IsZeroCellConfig<F> {
+    pub is_zero: IsZeroConfig<F>,
+    pub output: Column<Advice>,
+}
+
+impl<F: FieldExt> IsZeroCellConfig<F> {
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero.expr()
+    }
+}
+
+// IsZeroChip whose assign returns the boolean as an assigned cell
+pub struct IsZeroCellChip<F: FieldExt> {
+    config: IsZeroCellConfig<F>,
+}
+
+impl<F: FieldExt> IsZeroCellChip<F> {
+    pub fn construct(config: IsZeroCellConfig<F>) -> Self {
+        IsZeroCellChip { config }
+    }
+
+    // `output` gets equality enabled so the result can be copied out of the row
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> IsZeroCellConfig<F> {
+        let is_zero = IsZeroChip::configure(meta, &q_enable, value, value_inv);
+        meta.enable_equality(output);
+
+        meta.create_gate("is_zero output", |meta| {
+            // output = 1 - value * value_inv, which the is_zero gate forces to be 1 or 0
+            let q_enable = q_enable(meta);
+            let output = meta.query_advice(output, Rotation::cur());
+            vec![q_enable * (output - is_zero.expr())]
+        });
+
+        IsZeroCellConfig { is_zero, output }
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        value: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        IsZeroChip::construct(self.config.is_zero.clone()).assign(region, offset, value)?;
+        let is_zero = value.map(|value| if value == F::zero() { F::one() } else { F::zero() });
+        region.assign_advice(|| "is zero", self.config.output, offset, || is_zero)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestConfig {
+        q_enable: Selector,
+        value: Column<Advice>,
+        is_zero: IsZeroCellConfig<Fp>,
+        instance: Column<Instance>,
+    }
+
+    // Exposes value == 0 publicly. With `flip` the prover witnesses the wrong boolean
+    struct IsZeroCircuit {
+        value: Value<Fp>,
+        flip: bool,
+    }
+
+    impl Circuit<Fp> for IsZeroCircuit {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { value: Value::unknown(), flip: self.flip }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> TestConfig {
+            let q_enable = meta.selector();
+            let value = meta.advice_column();
+            let value_inv = meta.advice_column();
+            let output = meta.advice_column();
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let is_zero = IsZeroCellChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(value, Rotation::cur()),
+                value_inv,
+                output,
+            );
+            TestConfig { q_enable, value, is_zero, instance }
+        }
+
+        fn synthesize(&self, config: TestConfig, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = IsZeroCellChip::construct(config.is_zero.clone());
+            let out = layouter.assign_region(|| "is zero", |mut region| {
+                config.q_enable.enable(&mut region, 0)?;
+                region.assign_advice(|| "value", config.value, 0, || self.value)?;
+                if self.flip {
+                    IsZeroChip::construct(config.is_zero.is_zero.clone()).assign(&mut region, 0, self.value)?;
+                    let wrong = self.value.map(|value| if value == Fp::zero() { Fp::zero() } else { Fp::one() });
+                    region.assign_advice(|| "is zero", config.is_zero.output, 0, || wrong)
+                } else {
+                    chip.assign(&mut region, 0, self.value)
+                }
+            })?;
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    #[test]
+    fn is_zero_cell() {
+        for (value, expected) in [(0, 1), (1, 0), (5, 0)] {
+            let circuit = IsZeroCircuit { value: Value::known(Fp::from(value)), flip: false };
+            let prover = MockProver::run(3, &circuit, vec![vec![Fp::from(expected)]]).unwrap();
+            prover.assert_satisfied();
+
+            // the public result is copied from the cell, so the other boolean is rejected
+            let prover = MockProver::run(3, &circuit, vec![vec![Fp::from(1 - expected)]]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+    }
+
+    #[test]
+    fn is_zero_cell_rejects_wrong_boolean() {
+        // value = 5 with output = 1, the instance agrees with the lie so only the gate catches it
+        let circuit = IsZeroCircuit { value: Value::known(Fp::from(5)), flip: true };
+        let prover = MockProver::run(3, &circuit, vec![vec![Fp::one()]]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::ConstraintNotSatisfied {
+                constraint: ((1, "is_zero output").into(), 0, "").into(),
+                location: FailureLocation::InRegion { region: (0, "is zero").into(), offset: 0 },
+                // value and value_inv were queried by the is_zero gate, only the output shows here
+                cell_values: vec![(((Any::Advice, 2).into(), 0).into(), "1".to_string())],
+            }])
+        );
+    }
+}
@@ -0,0 +1,421 @@
+// Equality gadgets built on IsZeroChip, so the reasoning about value_inv lives in one place:
+// - IsEqualChip: lhs == rhs, that is lhs - rhs is zero
+// - IsNotEqualChip: lhs != rhs, the negation of the above
+// - AssertNonZeroChip: fails unless value != 0
+// lhs, rhs and value are expressions, so they can be cells of any column or computed from
+// several of them. The booleans are witnessed in an output cell that can be copied elsewhere,
+// and are also available as expressions for gates of the same row.
+// A config made with configure_cells instead has its own lhs and rhs columns and selector, and
+// assign_cells copies two cells assigned by other chips into them.
+//
+//  lhs | rhs | value_inv | output | q_enable
+// -----------------------------------------
+//  lhs | rhs | value_inv | output |    1
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+use super::{IsZeroCellChip, IsZeroCellConfig, IsZeroChip, IsZeroConfig};
+
+// The inputs of a config made with configure_cells
+#[derive(Clone, Debug)]
+pub struct CellInputs {
+    pub q_enable: Selector,
+    pub lhs: Column<Advice>,
+    pub rhs: Column<Advice>,
+}
+
+impl CellInputs {
+    fn configure<F: FieldExt>(meta: &mut ConstraintSystem<F>, lhs: Column<Advice>, rhs: Column<Advice>) -> Self {
+        meta.enable_equality(lhs);
+        meta.enable_equality(rhs);
+        CellInputs { q_enable: meta.selector(), lhs, rhs }
+    }
+
+    // Copies `lhs` and `rhs` in a region of one row, where `assign` witnesses the output
+    fn assign<F: FieldExt>(
+        inputs: &Option<Self>,
+        mut layouter: impl Layouter<F>,
+        name: &str,
+        lhs: &AssignedCell<F, F>,
+        rhs: &AssignedCell<F, F>,
+        assign: impl Fn(&mut Region<'_, F>, Value<F>, Value<F>) -> Result<AssignedCell<F, F>, Error>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let inputs = inputs.as_ref().expect("assign_cells needs configure_cells");
+        layouter.assign_region(|| name, |mut region| {
+            inputs.q_enable.enable(&mut region, 0)?;
+            let lhs = lhs.copy_advice(|| "lhs", &mut region, inputs.lhs, 0)?;
+            let rhs = rhs.copy_advice(|| "rhs", &mut region, inputs.rhs, 0)?;
+            assign(&mut region, lhs.value().copied(), rhs.value().copied())
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IsEqualConfig<F> {
+    pub is_zero: IsZeroCellConfig<F>,
+    // None unless configured with configure_cells
+    pub inputs: Option<CellInputs>,
+}
+
+impl<F: FieldExt> IsEqualConfig<F> {
+    pub fn expr(&self) -> Expression<F> {
+        self.is_zero.expr()
+    }
+}
+
+pub struct IsEqualChip<F: FieldExt> {
+    config: IsEqualConfig<F>,
+}
+
+impl<F: FieldExt> IsEqualChip<F> {
+    pub fn construct(config: IsEqualConfig<F>) -> Self {
+        IsEqualChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> IsEqualConfig<F> {
+        let is_zero = IsZeroCellChip::configure(meta, q_enable, |meta| lhs(meta) - rhs(meta), value_inv, output);
+        IsEqualConfig { is_zero, inputs: None }
+    }
+
+    // Compares the cells copied into `lhs` and `rhs` by assign_cells
+    pub fn configure_cells(
+        meta: &mut ConstraintSystem<F>,
+        lhs: Column<Advice>,
+        rhs: Column<Advice>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> IsEqualConfig<F> {
+        let inputs = CellInputs::configure(meta, lhs, rhs);
+        let q_enable = inputs.q_enable;
+        let mut config = Self::configure(
+            meta,
+            |meta| meta.query_selector(q_enable),
+            |meta| meta.query_advice(lhs, Rotation::cur()),
+            |meta| meta.query_advice(rhs, Rotation::cur()),
+            value_inv,
+            output,
+        );
+        config.inputs = Some(inputs);
+        config
+    }
+
+    // `lhs` and `rhs` are the values the expressions take at `offset`, the caller assigns them
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        IsZeroCellChip::construct(self.config.is_zero.clone()).assign(region, offset, lhs - rhs)
+    }
+
+    // Copies `lhs` and `rhs`, assigned by other chips, and returns the cell holding lhs == rhs
+    pub fn assign_cells(
+        &self,
+        layouter: impl Layouter<F>,
+        lhs: &AssignedCell<F, F>,
+        rhs: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        CellInputs::assign(&self.config.inputs, layouter, "is equal", lhs, rhs, |region, lhs, rhs| self.assign(region, 0, lhs, rhs))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IsNotEqualConfig<F> {
+    pub is_zero: IsZeroConfig<F>,
+    pub output: Column<Advice>,
+    // None unless configured with configure_cells
+    pub inputs: Option<CellInputs>,
+}
+
+impl<F: FieldExt> IsNotEqualConfig<F> {
+    pub fn expr(&self) -> Expression<F> {
+        Expression::Constant(F::one()) - self.is_zero.expr()
+    }
+}
+
+pub struct IsNotEqualChip<F: FieldExt> {
+    config: IsNotEqualConfig<F>,
+}
+
+impl<F: FieldExt> IsNotEqualChip<F> {
+    pub fn construct(config: IsNotEqualConfig<F>) -> Self {
+        IsNotEqualChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        lhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        rhs: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> IsNotEqualConfig<F> {
+        let is_zero = IsZeroChip::configure(meta, &q_enable, |meta| lhs(meta) - rhs(meta), value_inv);
+        meta.enable_equality(output);
+
+        let config = IsNotEqualConfig { is_zero, output, inputs: None };
+        meta.create_gate("is_not_equal output", |meta| {
+            // output = (lhs - rhs) * value_inv, which the is_zero gate forces to be 1 or 0
+            let q_enable = q_enable(meta);
+            let output = meta.query_advice(output, Rotation::cur());
+            vec![q_enable * (output - config.expr())]
+        });
+        config
+    }
+
+    // Compares the cells copied into `lhs` and `rhs` by assign_cells
+    pub fn configure_cells(
+        meta: &mut ConstraintSystem<F>,
+        lhs: Column<Advice>,
+        rhs: Column<Advice>,
+        value_inv: Column<Advice>,
+        output: Column<Advice>,
+    ) -> IsNotEqualConfig<F> {
+        let inputs = CellInputs::configure(meta, lhs, rhs);
+        let q_enable = inputs.q_enable;
+        let mut config = Self::configure(
+            meta,
+            |meta| meta.query_selector(q_enable),
+            |meta| meta.query_advice(lhs, Rotation::cur()),
+            |meta| meta.query_advice(rhs, Rotation::cur()),
+            value_inv,
+            output,
+        );
+        config.inputs = Some(inputs);
+        config
+    }
+
+    pub fn assign(
+        &self,
+        region: &mut Region<'_, F>,
+        offset: usize,
+        lhs: Value<F>,
+        rhs: Value<F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        IsZeroChip::construct(self.config.is_zero.clone()).assign(region, offset, lhs - rhs)?;
+        let is_not_equal = lhs.zip(rhs).map(|(lhs, rhs)| if lhs == rhs { F::zero() } else { F::one() });
+        region.assign_advice(|| "is not equal", self.config.output, offset, || is_not_equal)
+    }
+
+    // Copies `lhs` and `rhs`, assigned by other chips, and returns the cell holding lhs != rhs
+    pub fn assign_cells(
+        &self,
+        layouter: impl Layouter<F>,
+        lhs: &AssignedCell<F, F>,
+        rhs: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        CellInputs::assign(&self.config.inputs, layouter, "is not equal", lhs, rhs, |region, lhs, rhs| self.assign(region, 0, lhs, rhs))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct AssertNonZeroConfig<F> {
+    pub is_zero: IsZeroConfig<F>,
+}
+
+pub struct AssertNonZeroChip<F: FieldExt> {
+    config: AssertNonZeroConfig<F>,
+}
+
+impl<F: FieldExt> AssertNonZeroChip<F> {
+    pub fn construct(config: AssertNonZeroConfig<F>) -> Self {
+        AssertNonZeroChip { config }
+    }
+
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        q_enable: impl Fn(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value: impl FnOnce(&mut VirtualCells<'_, F>) -> Expression<F>,
+        value_inv: Column<Advice>,
+    ) -> AssertNonZeroConfig<F> {
+        let is_zero = IsZeroChip::configure(meta, &q_enable, value, value_inv);
+
+        meta.create_gate("assert_non_zero", |meta| {
+            // 1 - value * value_inv = 0 only has a solution if value has an inverse
+            let q_enable = q_enable(meta);
+            vec![q_enable * is_zero.expr()]
+        });
+        AssertNonZeroConfig { is_zero }
+    }
+
+    // A zero value can't be assigned so that the constraints hold, the proof fails
+    pub fn assign(&self, region: &mut Region<'_, F>, offset: usize, value: Value<F>) -> Result<(), Error> {
+        IsZeroChip::construct(self.config.is_zero.clone()).assign(region, offset, value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    #[derive(Clone, Debug)]
+    struct CompareConfig {
+        q_enable: Selector,
+        lhs: Column<Advice>,
+        rhs: Column<Advice>,
+        is_equal: IsEqualConfig<Fp>,
+        is_not_equal: IsNotEqualConfig<Fp>,
+        non_zero: AssertNonZeroConfig<Fp>,
+        instance: Column<Instance>,
+    }
+
+    // Exposes [lhs == rhs, lhs != rhs] and asserts that lhs is not zero
+    struct CompareCircuit {
+        lhs: Value<Fp>,
+        rhs: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for CompareCircuit {
+        type Config = CompareConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { lhs: Value::unknown(), rhs: Value::unknown() }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> CompareConfig {
+            let q_enable = meta.selector();
+            let [lhs, rhs, equal_inv, equal, not_equal_inv, not_equal, lhs_inv] = [(); 7].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(instance);
+
+            let is_equal = IsEqualChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(lhs, Rotation::cur()),
+                |meta| meta.query_advice(rhs, Rotation::cur()),
+                equal_inv,
+                equal,
+            );
+            let is_not_equal = IsNotEqualChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(lhs, Rotation::cur()),
+                |meta| meta.query_advice(rhs, Rotation::cur()),
+                not_equal_inv,
+                not_equal,
+            );
+            let non_zero = AssertNonZeroChip::configure(
+                meta,
+                |meta| meta.query_selector(q_enable),
+                |meta| meta.query_advice(lhs, Rotation::cur()),
+                lhs_inv,
+            );
+            CompareConfig { q_enable, lhs, rhs, is_equal, is_not_equal, non_zero, instance }
+        }
+
+        fn synthesize(&self, config: CompareConfig, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let (equal, not_equal) = layouter.assign_region(|| "compare", |mut region| {
+                config.q_enable.enable(&mut region, 0)?;
+                region.assign_advice(|| "lhs", config.lhs, 0, || self.lhs)?;
+                region.assign_advice(|| "rhs", config.rhs, 0, || self.rhs)?;
+
+                let equal = IsEqualChip::construct(config.is_equal.clone()).assign(&mut region, 0, self.lhs, self.rhs)?;
+                let not_equal = IsNotEqualChip::construct(config.is_not_equal.clone()).assign(&mut region, 0, self.lhs, self.rhs)?;
+                AssertNonZeroChip::construct(config.non_zero.clone()).assign(&mut region, 0, self.lhs)?;
+                Ok((equal, not_equal))
+            })?;
+            layouter.constrain_instance(equal.cell(), config.instance, 0)?;
+            layouter.constrain_instance(not_equal.cell(), config.instance, 1)
+        }
+    }
+
+    fn run(lhs: u64, rhs: u64, public_input: [u64; 2]) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = CompareCircuit { lhs: Value::known(Fp::from(lhs)), rhs: Value::known(Fp::from(rhs)) };
+        MockProver::run(4, &circuit, vec![public_input.map(Fp::from).to_vec()]).unwrap().verify()
+    }
+
+    #[test]
+    fn is_equal_and_is_not_equal() {
+        assert_eq!(run(3, 3, [1, 0]), Ok(()));
+        assert_eq!(run(3, 4, [0, 1]), Ok(()));
+        assert_eq!(run(4, 3, [0, 1]), Ok(()));
+
+        // the outputs are copied to the instance column, a wrong answer breaks the copy
+        assert!(run(3, 3, [0, 1]).is_err());
+        assert!(run(3, 4, [1, 0]).is_err());
+    }
+
+    #[test]
+    fn assert_non_zero() {
+        assert_eq!(
+            run(0, 4, [0, 1]),
+            Err(vec![VerifyFailure::ConstraintNotSatisfied {
+                constraint: ((5, "assert_non_zero").into(), 0, "").into(),
+                location: FailureLocation::InRegion { region: (0, "compare").into(), offset: 0 },
+                cell_values: vec![],
+            }])
+        );
+    }
+
+    #[derive(Clone, Debug)]
+    struct CellsConfig {
+        input: Column<Advice>,
+        is_equal: IsEqualConfig<Fp>,
+        is_not_equal: IsNotEqualConfig<Fp>,
+        instance: Column<Instance>,
+    }
+
+    // Same outputs as CompareCircuit, for lhs and rhs assigned in a region of their own
+    struct CellsCircuit {
+        lhs: Value<Fp>,
+        rhs: Value<Fp>,
+    }
+
+    impl Circuit<Fp> for CellsCircuit {
+        type Config = CellsConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { lhs: Value::unknown(), rhs: Value::unknown() }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> CellsConfig {
+            let [input, lhs, rhs, equal_inv, equal, not_equal_inv, not_equal] = [(); 7].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            let is_equal = IsEqualChip::configure_cells(meta, lhs, rhs, equal_inv, equal);
+            let is_not_equal = IsNotEqualChip::configure_cells(meta, lhs, rhs, not_equal_inv, not_equal);
+            CellsConfig { input, is_equal, is_not_equal, instance }
+        }
+
+        fn synthesize(&self, config: CellsConfig, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let (lhs, rhs) = layouter.assign_region(|| "inputs", |mut region| {
+                let lhs = region.assign_advice(|| "lhs", config.input, 0, || self.lhs)?;
+                let rhs = region.assign_advice(|| "rhs", config.input, 1, || self.rhs)?;
+                Ok((lhs, rhs))
+            })?;
+            let equal = IsEqualChip::construct(config.is_equal).assign_cells(layouter.namespace(|| "lhs == rhs"), &lhs, &rhs)?;
+            let not_equal = IsNotEqualChip::construct(config.is_not_equal).assign_cells(layouter.namespace(|| "lhs != rhs"), &lhs, &rhs)?;
+            layouter.constrain_instance(equal.cell(), config.instance, 0)?;
+            layouter.constrain_instance(not_equal.cell(), config.instance, 1)
+        }
+    }
+
+    #[test]
+    fn compare_cells() {
+        let run = |lhs: u64, rhs: u64, public_input: [u64; 2]| {
+            let circuit = CellsCircuit { lhs: Value::known(Fp::from(lhs)), rhs: Value::known(Fp::from(rhs)) };
+            MockProver::run(4, &circuit, vec![public_input.map(Fp::from).to_vec()]).unwrap().verify()
+        };
+        assert_eq!(run(3, 3, [1, 0]), Ok(()));
+        assert_eq!(run(3, 4, [0, 1]), Ok(()));
+        assert_eq!(run(0, 0, [1, 0]), Ok(()));
+        assert!(run(3, 3, [0, 1]).is_err());
+        assert!(run(3, 4, [1, 0]).is_err());
+    }
+}
@@ -0,0 +1,224 @@
+// f(a, b, c) = if a == b {c} else {a - b}, composed from smaller chips: the IsEqualChip checks
+// a == b, a gate computes a - b and the SelectChip picks c or a - b.
+//
+//  a | b | c | a - b | a == b | inv | output | selector | q_select
+// -----------------------------------------------------------------
+//  a | b | c |   d   |   eq   |  i  |        |    1     |    0
+//    |   | c |   d   |   eq   |     |   out  |    0     |    1
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{AssignedCell, Layouter, SimpleFloorPlanner, Value},
+    plonk::{Advice, Circuit, Column, ConstraintSystem, Error, Selector},
+    poly::Rotation,
+};
+
+use super::{IsEqualChip, IsEqualConfig};
+use crate::select::{SelectChip, SelectConfig};
+
+// The cells of a == b, c and a - b
+pub type Inputs<F> = (AssignedCell<F, F>, AssignedCell<F, F>, AssignedCell<F, F>);
+
+#[derive(Debug, Clone)]
+pub struct FunctionConfig<F: FieldExt> {
+    pub selector: Selector,
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub c: Column<Advice>,
+    pub diff: Column<Advice>,
+    pub a_equals_b: IsEqualConfig<F>,
+    pub select: SelectConfig,
+}
+
+#[derive(Debug, Clone)]
+pub struct FunctionChip<F: FieldExt> {
+    config: FunctionConfig<F>,
+}
+
+impl<F: FieldExt> FunctionChip<F> {
+    pub fn construct(config: FunctionConfig<F>) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> FunctionConfig<F> {
+        let selector = meta.selector();
+        let a = meta.advice_column();
+        let b = meta.advice_column();
+        let c = meta.advice_column();
+        let diff = meta.advice_column();
+        let output = meta.advice_column();
+
+        let is_zero_advice_column = meta.advice_column();
+        let a_equals_b_column = meta.advice_column();
+        let a_equals_b = IsEqualChip::configure(
+            meta,
+            |meta| meta.query_selector(selector),
+            |meta| meta.query_advice(a, Rotation::cur()),
+            |meta| meta.query_advice(b, Rotation::cur()),
+            is_zero_advice_column,
+            a_equals_b_column,
+        );
+
+        meta.create_gate("a - b", |meta| {
+            let s = meta.query_selector(selector);
+            let a = meta.query_advice(a, Rotation::cur());
+            let b = meta.query_advice(b, Rotation::cur());
+            let diff = meta.query_advice(diff, Rotation::cur());
+            vec![s * (diff - (a - b))]
+        });
+
+        // the select row reuses the columns of a == b, c and a - b, so the copies stay in place
+        let select = SelectChip::configure(meta, a_equals_b_column, c, diff, output);
+
+        FunctionConfig {
+            selector,
+            a,
+            b,
+            c,
+            diff,
+            a_equals_b,
+            select,
+        }
+    }
+
+    // Assigns a, b and c and returns the cells of a == b, c and a - b
+    pub fn assign_inputs(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: F,
+        b: F,
+        c: F,
+    ) -> Result<Inputs<F>, Error> {
+        let is_equal_chip = IsEqualChip::construct(self.config.a_equals_b.clone());
+
+        layouter.assign_region(
+            || "a == b and a - b",
+            |mut region| {
+                self.config.selector.enable(&mut region, 0)?;
+                region.assign_advice(|| "a", self.config.a, 0, || Value::known(a))?;
+                region.assign_advice(|| "b", self.config.b, 0, || Value::known(b))?;
+                let c = region.assign_advice(|| "c", self.config.c, 0, || Value::known(c))?;
+                let diff = region.assign_advice(|| "a - b", self.config.diff, 0, || Value::known(a - b))?;
+                let a_equals_b = is_equal_chip.assign(&mut region, 0, Value::known(a), Value::known(b))?;
+                Ok((a_equals_b, c, diff))
+            },
+        )
+    }
+
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        a: F,
+        b: F,
+        c: F,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let (a_equals_b, c, diff) = self.assign_inputs(layouter.namespace(|| "inputs"), a, b, c)?;
+        SelectChip::construct(self.config.select.clone()).assign(layouter.namespace(|| "select"), &a_equals_b, &c, &diff)
+    }
+}
+
+#[derive(Default)]
+pub struct FunctionCircuit<F> {
+    pub a: F,
+    pub b: F,
+    pub c: F,
+}
+
+impl<F: FieldExt> Circuit<F> for FunctionCircuit<F> {
+    type Config = FunctionConfig<F>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        FunctionChip::configure(meta)
+    }
+
+    fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
+        let chip = FunctionChip::construct(config);
+        chip.assign(layouter, self.a, self.b, self.c)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use proptest::prelude::*;
+
+    // The same function computed natively
+    fn function(a: Fp, b: Fp, c: Fp) -> Fp {
+        if a == b {
+            c
+        } else {
+            a - b
+        }
+    }
+
+    // Lays out f(a, b, c) like FunctionChip::assign, but selects any output the prover likes
+    struct OutputCircuit {
+        a: Fp,
+        b: Fp,
+        c: Fp,
+        output: Fp,
+    }
+
+    impl Circuit<Fp> for OutputCircuit {
+        type Config = FunctionConfig<Fp>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { a: Fp::zero(), b: Fp::zero(), c: Fp::zero(), output: Fp::zero() }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            FunctionChip::configure(meta)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let chip = FunctionChip::construct(config.clone());
+            let (a_equals_b, c, diff) = chip.assign_inputs(layouter.namespace(|| "inputs"), self.a, self.b, self.c)?;
+
+            let select = &config.select;
+            layouter.assign_region(
+                || "select",
+                |mut region| {
+                    select.q_select.enable(&mut region, 0)?;
+                    a_equals_b.copy_advice(|| "cond", &mut region, select.cond, 0)?;
+                    c.copy_advice(|| "x", &mut region, select.x, 0)?;
+                    diff.copy_advice(|| "y", &mut region, select.y, 0)?;
+                    region.assign_advice(|| "out", select.out, 0, || Value::known(self.output))?;
+                    Ok(())
+                },
+            )
+        }
+    }
+
+    // half of the cases have a == b, random a and b would almost never be equal
+    fn inputs() -> impl Strategy<Value = (u64, u64, u64)> {
+        prop_oneof![
+            any::<(u64, u64, u64)>(),
+            any::<(u64, u64)>().prop_map(|(a, c)| (a, a, c)),
+        ]
+    }
+
+    proptest! {
+        // The circuit accepts the output computed natively and nothing else
+        #[test]
+        fn function_matches_native((a, b, c) in inputs(), delta in 1u64..) {
+            let (a, b, c) = (Fp::from(a), Fp::from(b), Fp::from(c));
+            let output = function(a, b, c);
+
+            let prover = MockProver::run(4, &FunctionCircuit { a, b, c }, vec![]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+            let prover = MockProver::run(4, &OutputCircuit { a, b, c, output }, vec![]).unwrap();
+            prop_assert_eq!(prover.verify(), Ok(()));
+
+            let output = output + Fp::from(delta);
+            let prover = MockProver::run(4, &OutputCircuit { a, b, c, output }, vec![]).unwrap();
+            prop_assert!(prover.verify().is_err());
+        }
+    }
+}
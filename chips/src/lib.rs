@@ -0,0 +1,13 @@
+// Chips shared by the example crates of this workspace.
+// Each example binary used to carry its own copy of the chips it needs; they live here now so
+// circuits can depend on them and compose them.
+pub mod artifact;
+pub mod cost;
+pub mod examples;
+pub mod fibonacci;
+pub mod is_zero;
+pub mod prover;
+pub mod range_check;
+pub mod select;
+#[cfg(feature = "dev-graph")]
+pub mod render;
@@ -0,0 +1,122 @@
+// Real proving and verification for the Fibonacci circuits.
+// MockProver only tells us that the witness satisfies the constraints. Here we go through the
+// whole pipeline: generate the public parameters, run key generation, create a proof with a
+// Blake2b transcript and verify it, so a circuit that can't be proven for real is caught too.
+use std::{
+    fmt,
+    time::{Duration, Instant},
+};
+
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    pasta::{EqAffine, Fp},
+    plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ConstraintSystem, Error,
+        ProvingKey, SingleVerifier, VerifyingKey,
+    },
+    poly::commitment::Params,
+    transcript::{Blake2bRead, Blake2bWrite, Challenge255},
+};
+use rand_core::OsRng;
+
+// What it cost to prove and verify a circuit once.
+#[derive(Debug, Clone)]
+pub struct ProofReport {
+    pub k: u32,
+    pub proof_size: usize,
+    pub setup_time: Duration,
+    pub keygen_time: Duration,
+    pub proving_time: Duration,
+    pub verifying_time: Duration,
+}
+
+impl fmt::Display for ProofReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "k:          {}", self.k)?;
+        writeln!(f, "proof size: {} bytes", self.proof_size)?;
+        writeln!(f, "params:     {:?}", self.setup_time)?;
+        writeln!(f, "keygen:     {:?}", self.keygen_time)?;
+        writeln!(f, "prove:      {:?}", self.proving_time)?;
+        write!(f, "verify:     {:?}", self.verifying_time)
+    }
+}
+
+// Smallest k such that `rows` assigned rows fit in the circuit. The last rows of the 2^k are
+// reserved for the blinding factors, and how many depends on the columns and queries of `C`.
+pub fn min_k<F: FieldExt, C: Circuit<F>>(rows: usize) -> u32 {
+    let mut cs = ConstraintSystem::default();
+    C::configure(&mut cs);
+    let needed = std::cmp::max(rows + cs.blinding_factors() + 1, cs.minimum_rows());
+
+    let mut k = 1;
+    while (1 << k) < needed {
+        k += 1;
+    }
+    k
+}
+
+// The keys only depend on the shape of the circuit, so we generate them from the
+// witness-free version of it.
+pub fn keygen<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    circuit: &C,
+) -> Result<ProvingKey<EqAffine>, Error> {
+    let empty_circuit = circuit.without_witnesses();
+    let vk = keygen_vk(params, &empty_circuit)?;
+    keygen_pk(params, vk, &empty_circuit)
+}
+
+// `instances` holds one slice per instance column of the circuit.
+pub fn prove<C: Circuit<Fp>>(
+    params: &Params<EqAffine>,
+    pk: &ProvingKey<EqAffine>,
+    circuit: C,
+    instances: &[&[Fp]],
+) -> Result<Vec<u8>, Error> {
+    let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+    create_proof(params, pk, &[circuit], &[instances], OsRng, &mut transcript)?;
+    Ok(transcript.finalize())
+}
+
+pub fn verify(
+    params: &Params<EqAffine>,
+    vk: &VerifyingKey<EqAffine>,
+    instances: &[&[Fp]],
+    proof: &[u8],
+) -> Result<(), Error> {
+    let strategy = SingleVerifier::new(params);
+    let mut transcript = Blake2bRead::<_, EqAffine, Challenge255<_>>::init(proof);
+    verify_proof(params, vk, strategy, &[instances], &mut transcript)
+}
+
+// Runs the whole pipeline for `circuit` on 2^k rows and reports the proof size and timings.
+pub fn prove_and_verify<C: Circuit<Fp>>(
+    k: u32,
+    circuit: C,
+    instances: &[&[Fp]],
+) -> Result<ProofReport, Error> {
+    let start = Instant::now();
+    let params: Params<EqAffine> = Params::new(k);
+    let setup_time = start.elapsed();
+
+    let start = Instant::now();
+    let pk = keygen(&params, &circuit)?;
+    let keygen_time = start.elapsed();
+
+    let start = Instant::now();
+    let proof = prove(&params, &pk, circuit, instances)?;
+    let proving_time = start.elapsed();
+
+    let start = Instant::now();
+    verify(&params, pk.get_vk(), instances, &proof)?;
+    let verifying_time = start.elapsed();
+
+    Ok(ProofReport {
+        k,
+        proof_size: proof.len(),
+        setup_time,
+        keygen_time,
+        proving_time,
+        verifying_time,
+    })
+}
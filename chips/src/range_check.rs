@@ -0,0 +1,233 @@
+// This helper checks that the value witnessed in a given cell is within a given range.
+//layout: an advice column where you witness a value and a selector where you enable the range check constraint.
+/*Depending on the range, this helper uses either a range-check expression(for small ranges)
+    or lookup (for larger ranges)
+    If we have a very large R, then the polynomial is going to be very hugh degree and that will increase
+    the cost of the circuit
+*/
+//     value  | q_range_check | q_lookup | table_value
+// ----------------------------------------------------
+//      v     |       1       |     0     |     0
+//      v'    |       0       |     1     |     1
+/*For a table of LOOKUP_RANGE = 2^K values, v < 2^n with n < K is checked with the same table by
+    looking up v and v * 2^(K-n): the second one is only below 2^K if v < 2^n. The shifted value
+    is witnessed in the next row and q_shift[n] constrains it to be v * 2^(K-n). These selectors and
+    their gate only exist in configs made with configure_with_short
+*/
+//     value     | q_lookup | q_shift[n]
+// ---------------------------------------
+//      v        |     1    |     1
+//  v * 2^(K-n)  |     1    |     0
+// When writing configs, it's best practice to pass in advice columns beacause advice columns are very often shared across configs. 
+use halo2_proofs::{
+    plonk::*,
+    circuit::{AssignedCell, Layouter, Value},
+    arithmetic::FieldExt, poly::Rotation,
+};
+
+pub mod batch;
+pub mod circuit;
+pub mod less_than;
+pub mod limbs;
+pub mod running_sum;
+pub mod table;
+pub mod tagged;
+pub use batch::BatchRangeCheckConfig;
+pub use less_than::{LessThanChip, LessThanConfig};
+pub use limbs::LimbsConfig;
+pub use running_sum::RunningSumConfig;
+pub use table::RangeCheckTable;
+pub use tagged::{TaggedRangeCheckConfig, TaggedRangeCheckTable};
+
+// The cells of a batch of range checked values and the rows used
+pub type Batch<F> = (Vec<AssignedCell<F, F>>, usize);
+
+#[derive(Debug, Clone)]
+pub struct RangeCheckConfig<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize>{
+    pub value: Column<Advice>,
+    pub q_range_check: Selector,
+    pub q_lookup: Selector,
+    // one per n < K, empty unless configured with configure_with_short
+    pub q_shift: Vec<Selector>,
+    pub table: RangeCheckTable<F, LOOKUP_RANGE>
+}
+
+impl<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> RangeCheckConfig<F, RANGE, LOOKUP_RANGE>{
+    // the bits of the table values, the shifted lookups only work for a table of exactly 2^K values
+    pub const K: usize = if LOOKUP_RANGE.is_power_of_two() { LOOKUP_RANGE.trailing_zeros() as usize } else { 0 };
+
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self{
+        //Toggles the range check constraint
+        let q_range_check= meta.selector();
+
+        //Toggles the lookup argument
+        let q_lookup= meta.complex_selector();
+
+        //Cells assigned by other chips are copied into the value column
+        meta.enable_equality(value);
+
+        // Configure a lookup table
+        let table= RangeCheckTable::configure(meta);
+
+        let config= Self{
+            q_range_check,
+            value,
+            table: table.clone(),
+            q_lookup,
+            q_shift: vec![]
+        };
+
+        /* 
+            A single gate can have multiple constraints all toggled by the same selector. When you
+            have multiple constraints, it's best practice to name them.
+        */
+
+        // Range-check gate
+        // For  a value v and a range R, check that v < R
+        //v * (1 - v) * (2 - v) * ... * (R - 1 - v) = 0
+        /*notice: when we query a selector, we don't specify the rotation becasue by definition a 
+        //selector is always query at the current rotation and the advice columns that create relative
+        to the selectors offset*/
+        meta.create_gate("Range check", |meta|{
+            let q_range_check= meta.query_selector(q_range_check);
+            let value= meta.query_advice(value, Rotation::cur());
+
+            let range_check= |range: usize, value: Expression<F>|{
+                (0..range).fold(value.clone(), |expr, i|{
+                    expr * (Expression::Constant(F::from(i as u64)) - value.clone())
+                })
+            };
+            /*
+                Previously, we just returned a vector of expressions at the end of create_gate,
+                 Constraints::with_selector is doing the same thing. However, it's kind of 
+                 abstracting the selector away from you. So, you specify one selector and then behind
+                 the scenes it multiplies each expression by that selector. It is a cleaner way to do the
+                 same thing.
+             */
+            Constraints::with_selector(q_range_check, [("range_check", range_check(RANGE, value))])
+        });
+
+        //Range check lookup
+        //Check that a value v is contained within a lookup table of values 0..RANGE
+        //that's our lookup argument that we have to configure at key gen time
+        meta.lookup(|meta|{
+            let q_lookup= meta.query_selector(q_lookup);
+            let value= meta.query_advice(value, Rotation::cur());
+            vec![(q_lookup * value, table.value)]
+        });
+
+        config
+    }
+
+    // Same as configure, with the short range checks of assign_short on top. They cost K more
+    // selectors and a gate, so only the circuits that need them pay for them
+    pub fn configure_with_short(meta: &mut ConstraintSystem<F>, value: Column<Advice>) -> Self{
+        assert!(Self::K > 0, "the shifted lookups need a table of 2^K values");
+        let mut config= Self::configure(meta, value);
+
+        //Toggle the constraint between a value and its shifted copy, one for every shift
+        let q_shift: Vec<Selector>= (0..Self::K).map(|_| meta.selector()).collect();
+
+        //Short range check
+        //The next row holds v * 2^(K-n), both rows are looked up
+        meta.create_gate("Short range check", |meta|{
+            let v= meta.query_advice(value, Rotation::cur());
+            let shifted= meta.query_advice(value, Rotation::next());
+            q_shift.iter().enumerate().map(|(n, q)|{
+                let q= meta.query_selector(*q);
+                let shift= Expression::Constant(F::from(1 << (Self::K - n)));
+                ("shifted value", q * (shifted.clone() - v.clone() * shift))
+            }).collect::<Vec<_>>()
+        });
+
+        config.q_shift= q_shift;
+        config
+    }
+
+    /*
+    How can we make the configure and assign APIs better(well) connected?
+    They are pretty disjoint. We have to more or less remember the shape in which we configured
+    things and manually amke sure that we assign things in that exact shape. That's a lot of overhed
+    for the developer
+    */
+    pub fn assign(&self, mut layouter: impl Layouter<F>, value: Value<Assigned<F>>, range: usize) -> Result<(), Error>{
+        let (name, selector)= self.route(range);
+        layouter.assign_region(||name, |mut region|{
+            let offset= 0;
+            // Enable q_range_check or q_lookup
+            selector.enable(&mut region, offset)?;
+
+            //Assign given value
+            region.assign_advice(||"assign value", self.value, offset, ||value)?;
+            Ok(())
+        })
+    }
+
+    // Checks v < 2^num_bits for num_bits < K with the lookup table, no matter how small the bound
+    pub fn assign_short(&self, mut layouter: impl Layouter<F>, value: Value<Assigned<F>>, num_bits: usize) -> Result<(), Error>{
+        assert!(!self.q_shift.is_empty(), "short range checks need configure_with_short");
+        assert!(num_bits < Self::K, "the table checks {} bits, shorter ranges only", Self::K);
+        layouter.assign_region(||"Assign value for short range check", |mut region|{
+            // Enable q_lookup on both rows and the shift by 2^(K - num_bits)
+            self.q_lookup.enable(&mut region, 0)?;
+            self.q_lookup.enable(&mut region, 1)?;
+            self.q_shift[num_bits].enable(&mut region, 0)?;
+
+            //Assign given value and its shifted copy
+            region.assign_advice(||"assign value", self.value, 0, ||value)?;
+            let shift= Assigned::from(F::from(1 << (Self::K - num_bits)));
+            region.assign_advice(||"assign shifted value", self.value, 1, ||value.map(|v| v * shift))?;
+            Ok(())
+        })
+    }
+
+    // Same as assign, for a cell another chip already assigned: it is copied into the range
+    // check region and the copy is returned
+    pub fn assign_cell(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, range: usize) -> Result<AssignedCell<F, F>, Error>{
+        let (name, selector)= self.route(range);
+        layouter.assign_region(||name, |mut region|{
+            selector.enable(&mut region, 0)?;
+            cell.copy_advice(||"assign value", &mut region, self.value, 0)
+        })
+    }
+
+    // The region name and selector of assign and assign_cell. `range` is the bound the value must
+    // stay below: up to RANGE the gate checks v < RANGE, above it the lookup checks v < LOOKUP_RANGE
+    fn route(&self, range: usize) -> (&'static str, Selector){
+        assert!(range <= LOOKUP_RANGE);
+        if range <= RANGE {
+            ("Assign value", self.q_range_check)
+        } else {
+            ("Assign value for lookup range check", self.q_lookup)
+        }
+    }
+
+    // Same as assign_short, for a cell another chip already assigned. Returns the copy
+    pub fn assign_short_cell(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, num_bits: usize) -> Result<AssignedCell<F, F>, Error>{
+        assert!(!self.q_shift.is_empty(), "short range checks need configure_with_short");
+        assert!(num_bits < Self::K, "the table checks {} bits, shorter ranges only", Self::K);
+        layouter.assign_region(||"Assign value for short range check", |mut region|{
+            self.q_lookup.enable(&mut region, 0)?;
+            self.q_lookup.enable(&mut region, 1)?;
+            self.q_shift[num_bits].enable(&mut region, 0)?;
+
+            let value= cell.copy_advice(||"assign value", &mut region, self.value, 0)?;
+            let shift= F::from(1 << (Self::K - num_bits));
+            region.assign_advice(||"assign shifted value", self.value, 1, ||value.value().map(|v| *v * shift))?;
+            Ok(value)
+        })
+    }
+
+    // Checks every value with the lookup, one value per row of a single region instead of a
+    // region per value. Returns the cells and the rows used
+    pub fn assign_batch(&self, mut layouter: impl Layouter<F>, values: &[Value<F>]) -> Result<Batch<F>, Error>{
+        layouter.assign_region(||"Assign values for batch range check", |mut region|{
+            let mut cells= Vec::with_capacity(values.len());
+            for (offset, value) in values.iter().enumerate(){
+                self.q_lookup.enable(&mut region, offset)?;
+                cells.push(region.assign_advice(||"assign value", self.value, offset, ||*value)?);
+            }
+            Ok((cells, values.len()))
+        })
+    }
+}
@@ -0,0 +1,141 @@
+// Range checks many values in a single region, COLUMNS values per row, every column being looked
+// up in a RangeCheckTable of RANGE values. Checking thousands of bytes takes len / COLUMNS rows
+// instead of a region per value.
+//
+//  v_0 | v_1 | ... | v_{COLUMNS-1} | q_lookup
+// ---------------------------------------------
+//  v_0 | v_1 | ... | v_{COLUMNS-1} |    1
+//  v_C | ... |     |               |    1
+// The last row is padded with zeros.
+use halo2_proofs::{
+    plonk::*,
+    circuit::{Layouter, Value},
+    arithmetic::FieldExt, poly::Rotation,
+};
+
+use super::{Batch, RangeCheckTable};
+
+#[derive(Debug, Clone)]
+pub struct BatchRangeCheckConfig<F: FieldExt, const RANGE: usize, const COLUMNS: usize>{
+    pub values: [Column<Advice>; COLUMNS],
+    pub q_lookup: Selector,
+    pub table: RangeCheckTable<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize, const COLUMNS: usize> BatchRangeCheckConfig<F, RANGE, COLUMNS>{
+    // The table is passed in so several configs can share it, it must be loaded by the circuit
+    pub fn configure(meta: &mut ConstraintSystem<F>, values: [Column<Advice>; COLUMNS], table: RangeCheckTable<F, RANGE>) -> Self{
+        assert!(COLUMNS > 0, "at least one column");
+
+        //Toggles the lookups of every column of the row
+        let q_lookup= meta.complex_selector();
+
+        for value in values{
+            meta.enable_equality(value);
+            meta.lookup(|meta|{
+                let q= meta.query_selector(q_lookup);
+                let value= meta.query_advice(value, Rotation::cur());
+                vec![(q * value, table.value)]
+            });
+        }
+
+        Self { values, q_lookup, table }
+    }
+
+    // Returns the cells of the values, in order, and the rows used
+    pub fn assign(&self, mut layouter: impl Layouter<F>, values: &[Value<F>]) -> Result<Batch<F>, Error>{
+        let rows= values.len().div_ceil(COLUMNS);
+        layouter.assign_region(||"batch range check", |mut region|{
+            let mut cells= Vec::with_capacity(values.len());
+            for offset in 0..rows{
+                self.q_lookup.enable(&mut region, offset)?;
+                for (i, column) in self.values.iter().enumerate(){
+                    match values.get(offset * COLUMNS + i){
+                        Some(value) => cells.push(region.assign_advice(||"value", *column, offset, ||*value)?),
+                        None => {
+                            region.assign_advice(||"padding", *column, offset, ||Value::known(F::zero()))?;
+                        }
+                    }
+                }
+            }
+            Ok((cells, rows))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    // Checks that every value is a byte, COLUMNS at a time
+    struct BytesCircuit<const COLUMNS: usize> {
+        values: Vec<u64>,
+    }
+
+    impl<const COLUMNS: usize> Circuit<Fp> for BytesCircuit<COLUMNS> {
+        type Config = BatchRangeCheckConfig<Fp, 256, COLUMNS>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { values: vec![0; self.values.len()] }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let values = [(); COLUMNS].map(|_| meta.advice_column());
+            let table = RangeCheckTable::configure(meta);
+            BatchRangeCheckConfig::configure(meta, values, table)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let values: Vec<_> = self.values.iter().map(|v| Value::known(Fp::from(*v))).collect();
+            let (cells, rows) = config.assign(layouter.namespace(|| "bytes"), &values)?;
+            assert_eq!(cells.len(), values.len());
+            assert_eq!(rows, values.len().div_ceil(COLUMNS));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn batch_of_bytes() {
+        // 1000 bytes in 250 rows of 4
+        let values: Vec<u64> = (0..1000).map(|i| (i * 7) % 256).collect();
+        let prover = MockProver::run(9, &BytesCircuit::<4> { values: values.clone() }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // a last row with a single value
+        let prover = MockProver::run(9, &BytesCircuit::<3> { values: values.clone() }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // one column, nearly every usable row of k = 9
+        let values: Vec<u64> = (0..500).map(|i| i % 256).collect();
+        let prover = MockProver::run(9, &BytesCircuit::<1> { values }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+    }
+
+    #[test]
+    fn batch_rejects_large_value() {
+        // value 6 is in the column 2 of row 1
+        let mut values: Vec<u64> = (0..10).collect();
+        values[6] = 256;
+        let prover = MockProver::run(9, &BytesCircuit::<4> { values }, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 2,
+                location: FailureLocation::InRegion { region: (1, "batch range check").into(), offset: 1 },
+            }])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one column")]
+    fn batch_needs_a_column() {
+        let _ = MockProver::run(9, &BytesCircuit::<0> { values: vec![1] }, vec![]);
+    }
+}
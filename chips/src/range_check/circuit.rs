@@ -0,0 +1,283 @@
+// A small value checked with the range check gate and a larger one with the lookup table
+use halo2_proofs::{
+    arithmetic::FieldExt,
+    circuit::{floor_planner::V1, Layouter, Value},
+    plonk::{Assigned, Circuit, ConstraintSystem, Error},
+};
+
+use super::RangeCheckConfig;
+
+#[derive(Default)]
+pub struct RangeCheckCircuit<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> {
+    pub value: Value<Assigned<F>>,
+    pub large_value: Value<Assigned<F>>,
+}
+
+impl<F: FieldExt, const RANGE: usize, const LOOKUP_RANGE: usize> Circuit<F> for RangeCheckCircuit<F, RANGE, LOOKUP_RANGE> {
+    type Config = RangeCheckConfig<F, RANGE, LOOKUP_RANGE>;
+    type FloorPlanner = V1;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+        let value = meta.advice_column();
+        RangeCheckConfig::configure(meta, value)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<F>,
+    ) -> Result<(), Error> {
+        config.table.load(&mut layouter)?;
+        config.assign(layouter.namespace(|| "Assign value"), self.value, RANGE)?;
+        config.assign(layouter.namespace(|| "Assign larger value"), self.large_value, LOOKUP_RANGE)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{cost::cost_report, fibonacci::three_column::{FiboChip, FiboConfig}};
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+        plonk::{Any, Column, Instance},
+    };
+    use proptest::prelude::*;
+
+    const RANGE: usize = 8;
+    const LOOKUP_RANGE: usize = 256;
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        // Values on both sides of the two ranges: the circuit accepts them exactly when value is
+        // below RANGE and large_value below LOOKUP_RANGE
+        #[test]
+        fn range_check_matches_native(value in 0u64..2 * RANGE as u64, large_value in 0u64..2 * LOOKUP_RANGE as u64) {
+            let circuit = RangeCheckCircuit::<Fp, RANGE, LOOKUP_RANGE> {
+                value: Value::known(Fp::from(value).into()),
+                large_value: Value::known(Fp::from(large_value).into()),
+            };
+            let in_range = value < RANGE as u64 && large_value < LOOKUP_RANGE as u64;
+
+            let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+            prop_assert_eq!(prover.verify().is_ok(), in_range);
+        }
+    }
+
+    #[test]
+    fn range_equal_to_range_takes_the_gate() {
+        // assign(value, RANGE) must reject RANGE itself, which the lookup table would accept
+        let circuit = RangeCheckCircuit::<Fp, RANGE, LOOKUP_RANGE> {
+            value: Value::known(Fp::from(RANGE as u64).into()),
+            large_value: Value::known(Fp::zero().into()),
+        };
+        assert_eq!(
+            MockProver::run(9, &circuit, vec![]).unwrap().verify(),
+            Err(vec![VerifyFailure::ConstraintNotSatisfied {
+                constraint: ((0, "Range check").into(), 0, "range_check").into(),
+                location: FailureLocation::InRegion { region: (1, "Assign value").into(), offset: 0 },
+                cell_values: vec![(((Any::Advice, 0).into(), 0).into(), "0x8".to_string())],
+            }])
+        );
+    }
+
+    // Checks value < 2^NUM_BITS with the 256 values table. With `shifted` the prover witnesses
+    // its own shifted copy
+    struct ShortRangeCheckCircuit<const NUM_BITS: usize> {
+        value: u64,
+        shifted: Option<u64>,
+    }
+
+    impl<const NUM_BITS: usize> Circuit<Fp> for ShortRangeCheckCircuit<NUM_BITS> {
+        type Config = RangeCheckConfig<Fp, RANGE, LOOKUP_RANGE>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { value: 0, shifted: self.shifted }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckConfig::configure_with_short(meta, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let value = Value::known(Fp::from(self.value).into());
+            let shifted = match self.shifted {
+                Some(shifted) => shifted,
+                None => return config.assign_short(layouter.namespace(|| "Assign short value"), value, NUM_BITS),
+            };
+            layouter.assign_region(|| "Assign value for short range check", |mut region| {
+                config.q_lookup.enable(&mut region, 0)?;
+                config.q_lookup.enable(&mut region, 1)?;
+                config.q_shift[NUM_BITS].enable(&mut region, 0)?;
+                region.assign_advice(|| "assign value", config.value, 0, || value)?;
+                region.assign_advice(|| "assign shifted value", config.value, 1, || Value::known(Fp::from(shifted)))?;
+                Ok(())
+            })
+        }
+    }
+
+    fn short<const NUM_BITS: usize>(value: u64, shifted: Option<u64>) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = ShortRangeCheckCircuit::<NUM_BITS> { value, shifted };
+        MockProver::run(9, &circuit, vec![]).unwrap().verify()
+    }
+
+    #[test]
+    fn short_range_check_boundaries() {
+        fn boundaries<const NUM_BITS: usize>() {
+            assert_eq!(short::<NUM_BITS>((1 << NUM_BITS) - 1, None), Ok(()), "{} bits", NUM_BITS);
+            // 2^n is in the table, 2^n * 2^(8-n) isn't
+            assert_eq!(
+                short::<NUM_BITS>(1 << NUM_BITS, None),
+                Err(vec![VerifyFailure::Lookup {
+                    lookup_index: 0,
+                    location: FailureLocation::InRegion { region: (1, "Assign value for short range check").into(), offset: 1 },
+                }]),
+                "{} bits",
+                NUM_BITS
+            );
+        }
+        boundaries::<0>();
+        boundaries::<1>();
+        boundaries::<4>();
+        boundaries::<7>();
+        assert_eq!(short::<0>(0, None), Ok(()));
+    }
+
+    #[test]
+    fn short_range_check_is_opt_in() {
+        let plain = cost_report("plain", 9, &RangeCheckCircuit::<Fp, RANGE, LOOKUP_RANGE>::default()).unwrap();
+        assert_eq!((plain.selectors, plain.gates), (2, 1));
+
+        // K = 8 shifts and their gate
+        let short = cost_report("short", 9, &ShortRangeCheckCircuit::<4> { value: 0, shifted: None }).unwrap();
+        assert_eq!((short.selectors, short.gates), (10, 2));
+    }
+
+    #[test]
+    fn short_range_check_rejects_wrong_shift() {
+        // 16 in 4 bits with a shifted copy that is in the table
+        assert_eq!(
+            short::<4>(16, Some(16)),
+            Err(vec![VerifyFailure::ConstraintNotSatisfied {
+                constraint: ((1, "Short range check").into(), 4, "shifted value").into(),
+                location: FailureLocation::InRegion { region: (1, "Assign value for short range check").into(), offset: 0 },
+                cell_values: vec![
+                    (((Any::Advice, 0).into(), 0).into(), "0x10".to_string()),
+                    (((Any::Advice, 0).into(), 1).into(), "0x10".to_string()),
+                ],
+            }])
+        );
+    }
+
+    // Range checks Fibonacci terms assigned by FiboChip: F[2] = 2 with the gate and in 2 bits,
+    // the last term with the lookup. The checked copy of the last term is exposed
+    struct FiboRangeCheckCircuit {
+        rows: usize,
+    }
+
+    impl Circuit<Fp> for FiboRangeCheckCircuit {
+        type Config = (FiboConfig, RangeCheckConfig<Fp, RANGE, LOOKUP_RANGE>, Column<Instance>);
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { rows: self.rows }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let advice = [(); 3].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            let value = meta.advice_column();
+            (FiboChip::configure(meta, advice, instance), RangeCheckConfig::configure_with_short(meta, value), instance)
+        }
+
+        fn synthesize(&self, (fibo, range_check, instance): Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            range_check.table.load(&mut layouter)?;
+            let chip = FiboChip::construct(fibo);
+            let (_, mut prev_b, mut prev_c) = chip.assign_first_row(layouter.namespace(|| "first row"), Value::known(Fp::one()), Value::known(Fp::one()))?;
+            range_check.assign_cell(layouter.namespace(|| "F[2] < RANGE"), &prev_c.0, RANGE)?;
+            range_check.assign_short_cell(layouter.namespace(|| "F[2] < 2^2"), &prev_c.0, 2)?;
+
+            for _ in 0..self.rows {
+                let c = chip.assign_row(layouter.namespace(|| "next row"), &prev_b, &prev_c)?;
+                prev_b = prev_c;
+                prev_c = c;
+            }
+            let checked = range_check.assign_cell(layouter.namespace(|| "last term < LOOKUP_RANGE"), &prev_c.0, LOOKUP_RANGE)?;
+            layouter.constrain_instance(checked.cell(), instance, 0)
+        }
+    }
+
+    #[test]
+    fn range_check_fibonacci_terms() {
+        // F[12] = 233 fits in the table, F[13] = 377 doesn't
+        let prover = MockProver::run(9, &FiboRangeCheckCircuit { rows: 10 }, vec![vec![Fp::from(233)]]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        // the table, the first row, the two checks of F[2] and a region per row come first
+        let rows = 11;
+        let prover = MockProver::run(9, &FiboRangeCheckCircuit { rows }, vec![vec![Fp::from(377)]]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 0,
+                location: FailureLocation::InRegion { region: (4 + rows, "Assign value for lookup range check").into(), offset: 0 },
+            }])
+        );
+
+        // the exposed cell is a copy of the last term
+        let prover = MockProver::run(9, &FiboRangeCheckCircuit { rows: 10 }, vec![vec![Fp::from(234)]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Checks every value in the single column of RangeCheckConfig
+    struct BatchCircuit {
+        values: Vec<u64>,
+    }
+
+    impl Circuit<Fp> for BatchCircuit {
+        type Config = RangeCheckConfig<Fp, RANGE, LOOKUP_RANGE>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { values: vec![0; self.values.len()] }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let value = meta.advice_column();
+            RangeCheckConfig::configure(meta, value)
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.table.load(&mut layouter)?;
+            let values: Vec<_> = self.values.iter().map(|v| Value::known(Fp::from(*v))).collect();
+            let (cells, rows) = config.assign_batch(layouter.namespace(|| "Assign values"), &values)?;
+            assert_eq!((cells.len(), rows), (values.len(), values.len()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn batch_range_check() {
+        let prover = MockProver::run(9, &BatchCircuit { values: (0..300).map(|i| i % 256).collect() }, vec![]).unwrap();
+        assert_eq!(prover.verify(), Ok(()));
+
+        let prover = MockProver::run(9, &BatchCircuit { values: vec![3, 255, 256, 0] }, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 0,
+                location: FailureLocation::InRegion { region: (1, "Assign values for batch range check").into(), offset: 2 },
+            }])
+        );
+    }
+}
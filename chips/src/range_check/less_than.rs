@@ -0,0 +1,294 @@
+// a < b for values of N bits, witnessed as a boolean cell. N is picked at configure time and takes
+// LIMBS = ceil(N / 8) limbs, 8 * (LIMBS - 1) < N <= 8 * LIMBS.
+// With a, b < 2^N, the difference
+//     diff = b - a - 1 + 2^N
+// is in [2^N, 2^(N+1)) when a < b and in [0, 2^N) otherwise, so lt is its bit N:
+//     diff = lt * 2^N + l_0 + l_1 * 2^8 + ... + l_{LIMBS-1} * 2^(8 * (LIMBS - 1))
+// with every limb looked up in the RangeCheckTable of 256 values. When N isn't a multiple of 8 the
+// top limb has r = N - 8 * (LIMBS - 1) bits only: it is also looked up shifted by 2^(8 - r), which
+// is only in the table below 2^r (see the short range checks of range_check.rs). a > b is lt(b, a).
+// The chip doesn't check that a and b fit in N bits, they must be range checked already (with
+// LimbsConfig for example), otherwise the result means nothing.
+//
+//  a | b | lt | l_0 | ... | l_{LIMBS-1} | q_lt
+// -------------------------------------------------
+//  a | b | lt | l_0 | ... | l_{LIMBS-1} |  1
+use halo2_proofs::{
+    plonk::*,
+    circuit::{AssignedCell, Layouter},
+    arithmetic::FieldExt, poly::Rotation,
+};
+
+use super::{limbs::LIMB_BITS, RangeCheckTable};
+
+#[derive(Debug, Clone)]
+pub struct LessThanConfig<F: FieldExt, const LIMBS: usize>{
+    pub a: Column<Advice>,
+    pub b: Column<Advice>,
+    pub lt: Column<Advice>,
+    pub limbs: [Column<Advice>; LIMBS],
+    pub q_lt: Selector,
+    pub table: RangeCheckTable<F, 256>,
+    // N, the bits of a and b
+    pub num_bits: usize,
+}
+
+pub struct LessThanChip<F: FieldExt, const LIMBS: usize>{
+    config: LessThanConfig<F, LIMBS>,
+}
+
+impl<F: FieldExt, const LIMBS: usize> LessThanChip<F, LIMBS>{
+    pub fn construct(config: LessThanConfig<F, LIMBS>) -> Self {
+        Self { config }
+    }
+
+    // The table is passed in so several configs can share it, it must be loaded by the circuit
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        a: Column<Advice>,
+        b: Column<Advice>,
+        lt: Column<Advice>,
+        limbs: [Column<Advice>; LIMBS],
+        table: RangeCheckTable<F, 256>,
+        num_bits: usize,
+    ) -> LessThanConfig<F, LIMBS>{
+        // diff is computed from its lower 128 bits and has N + 1 of them
+        assert!((1..16).contains(&LIMBS), "at most 120 bits");
+        assert!(
+            num_bits > LIMB_BITS * (LIMBS - 1) && num_bits <= LIMB_BITS * LIMBS,
+            "{} bits don't take {} limbs", num_bits, LIMBS
+        );
+        let top_bits= num_bits - LIMB_BITS * (LIMBS - 1);
+
+        //Toggles the decomposition gate and the lookups of the limbs
+        let q_lt= meta.complex_selector();
+
+        meta.enable_equality(a);
+        meta.enable_equality(b);
+        meta.enable_equality(lt);
+
+        meta.create_gate("less than", |meta|{
+            let q= meta.query_selector(q_lt);
+            let a= meta.query_advice(a, Rotation::cur());
+            let b= meta.query_advice(b, Rotation::cur());
+            let lt= meta.query_advice(lt, Rotation::cur());
+
+            let two_pow_n= Expression::Constant(F::from_u128(1 << num_bits));
+            let diff= b - a - Expression::Constant(F::one()) + two_pow_n.clone();
+            let sum= limbs.iter().enumerate().fold(lt.clone() * two_pow_n, |sum, (i, limb)|{
+                let limb= meta.query_advice(*limb, Rotation::cur());
+                sum + limb * Expression::Constant(F::from_u128(1 << (LIMB_BITS * i)))
+            });
+            Constraints::with_selector(q, [
+                ("lt is a bit", lt.clone() * (Expression::Constant(F::one()) - lt)),
+                ("diff is lt * 2^N plus its limbs", diff - sum),
+            ])
+        });
+
+        for limb in limbs{
+            meta.lookup(|meta|{
+                let q= meta.query_selector(q_lt);
+                let limb= meta.query_advice(limb, Rotation::cur());
+                vec![(q * limb, table.value)]
+            });
+        }
+
+        //The top limb shifted by 2^(8 - r) is only in the table if it is below 2^r
+        if top_bits < LIMB_BITS{
+            meta.lookup(|meta|{
+                let q= meta.query_selector(q_lt);
+                let top= meta.query_advice(limbs[LIMBS - 1], Rotation::cur());
+                let shift= Expression::Constant(F::from(1 << (LIMB_BITS - top_bits)));
+                vec![(q * top * shift, table.value)]
+            });
+        }
+
+        LessThanConfig { a, b, lt, limbs, q_lt, table, num_bits }
+    }
+
+    // Copies `a` and `b` and returns the cell holding a < b
+    pub fn assign(&self, mut layouter: impl Layouter<F>, a: &AssignedCell<F, F>, b: &AssignedCell<F, F>) -> Result<AssignedCell<F, F>, Error>{
+        let config= &self.config;
+        layouter.assign_region(||"less than", |mut region|{
+            config.q_lt.enable(&mut region, 0)?;
+
+            let a= a.copy_advice(||"a", &mut region, config.a, 0)?;
+            let b= b.copy_advice(||"b", &mut region, config.b, 0)?;
+
+            // only below 2^(N+1) when a and b fit in N bits, the lookups reject the limbs otherwise
+            let diff= a.value().zip(b.value()).map(|(a, b)|{
+                (*b - *a - F::one() + F::from_u128(1 << config.num_bits)).get_lower_128()
+            });
+
+            // the limbs only split the lower N bits, bit N is lt
+            let rest= diff.map(|d| d & ((1 << config.num_bits) - 1));
+            for i in 0..LIMBS{
+                let limb= rest.map(|d| F::from_u128((d >> (LIMB_BITS * i)) & 0xff));
+                region.assign_advice(||format!("limb {}", i), config.limbs[i], 0, ||limb)?;
+            }
+            region.assign_advice(||"lt", config.lt, 0, ||diff.map(|d| F::from_u128(d >> config.num_bits)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::{SimpleFloorPlanner, Value},
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    #[derive(Debug, Clone)]
+    struct TestConfig<const LIMBS: usize> {
+        input: Column<Advice>,
+        less_than: LessThanConfig<Fp, LIMBS>,
+        instance: Column<Instance>,
+    }
+
+    // Exposes [a < b, a > b] for BITS bits values. With `flip` the prover claims the opposite of
+    // a < b and fills the limbs so that the sum still holds
+    struct CompareCircuit<const LIMBS: usize, const BITS: usize> {
+        a: u64,
+        b: u64,
+        flip: bool,
+    }
+
+    impl<const LIMBS: usize, const BITS: usize> Circuit<Fp> for CompareCircuit<LIMBS, BITS> {
+        type Config = TestConfig<LIMBS>;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { a: 0, b: 0, flip: self.flip }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+            let input = meta.advice_column();
+            let [a, b, lt] = [(); 3].map(|_| meta.advice_column());
+            let limbs = [(); LIMBS].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            let table = RangeCheckTable::configure(meta);
+            let less_than = LessThanChip::configure(meta, a, b, lt, limbs, table, BITS);
+            TestConfig { input, less_than, instance }
+        }
+
+        fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.less_than.table.load(&mut layouter)?;
+            let (a, b) = layouter.assign_region(|| "inputs", |mut region| {
+                let a = region.assign_advice(|| "a", config.input, 0, || Value::known(Fp::from(self.a)))?;
+                let b = region.assign_advice(|| "b", config.input, 1, || Value::known(Fp::from(self.b)))?;
+                Ok((a, b))
+            })?;
+
+            let chip = LessThanChip::construct(config.less_than.clone());
+            let lt = if self.flip {
+                let lc = &config.less_than;
+                layouter.assign_region(|| "less than", |mut region| {
+                    lc.q_lt.enable(&mut region, 0)?;
+                    a.copy_advice(|| "a", &mut region, lc.a, 0)?;
+                    b.copy_advice(|| "b", &mut region, lc.b, 0)?;
+                    // lt * 2^N + limbs = diff with the wrong lt, the limbs wrap around the field
+                    let lt = if self.a < self.b { Fp::zero() } else { Fp::one() };
+                    let two_pow_n = Fp::from_u128(1 << BITS);
+                    let rest = Fp::from(self.b) - Fp::from(self.a) - Fp::one() + two_pow_n - lt * two_pow_n;
+                    region.assign_advice(|| "limb 0", lc.limbs[0], 0, || Value::known(rest))?;
+                    for i in 1..LIMBS {
+                        region.assign_advice(|| format!("limb {}", i), lc.limbs[i], 0, || Value::known(Fp::zero()))?;
+                    }
+                    region.assign_advice(|| "lt", lc.lt, 0, || Value::known(lt))
+                })?
+            } else {
+                chip.assign(layouter.namespace(|| "a < b"), &a, &b)?
+            };
+            let gt = chip.assign(layouter.namespace(|| "a > b"), &b, &a)?;
+
+            layouter.constrain_instance(lt.cell(), config.instance, 0)?;
+            layouter.constrain_instance(gt.cell(), config.instance, 1)
+        }
+    }
+
+    fn compare<const LIMBS: usize, const BITS: usize>(a: u64, b: u64) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = CompareCircuit::<LIMBS, BITS> { a, b, flip: false };
+        let public_input = vec![Fp::from((a < b) as u64), Fp::from((a > b) as u64)];
+        MockProver::run(9, &circuit, vec![public_input]).unwrap().verify()
+    }
+
+    #[test]
+    fn less_than_boundaries() {
+        let max = 255;
+        for (a, b) in [(0, 0), (0, 1), (1, 0), (max - 1, max), (max, max - 1), (max, max), (0, max), (max, 0)] {
+            assert_eq!(compare::<1, 8>(a, b), Ok(()), "{} < {}", a, b);
+        }
+
+        let max = u16::MAX as u64;
+        for (a, b) in [(0, 0), (255, 256), (256, 255), (max - 1, max), (max, max - 1), (max, max), (0, max), (max, 0)] {
+            assert_eq!(compare::<2, 16>(a, b), Ok(()), "{} < {}", a, b);
+        }
+
+        let max = u64::MAX;
+        for (a, b) in [(max - 1, max), (max, max - 1), (max, max), (0, max)] {
+            assert_eq!(compare::<8, 64>(a, b), Ok(()), "{} < {}", a, b);
+        }
+
+        // bit widths that aren't a multiple of 8
+        let max = (1 << 4) - 1;
+        for (a, b) in [(0, 0), (0, 1), (1, 0), (max - 1, max), (max, max - 1), (max, max), (0, max), (max, 0)] {
+            assert_eq!(compare::<1, 4>(a, b), Ok(()), "{} < {}", a, b);
+        }
+        let max = (1 << 12) - 1;
+        for (a, b) in [(0, 0), (255, 256), (256, 255), (max - 1, max), (max, max - 1), (max, max), (0, max), (max, 0)] {
+            assert_eq!(compare::<2, 12>(a, b), Ok(()), "{} < {}", a, b);
+        }
+        let max = 1;
+        for (a, b) in [(0, 0), (0, max), (max, 0), (max, max)] {
+            assert_eq!(compare::<1, 1>(a, b), Ok(()), "{} < {}", a, b);
+        }
+    }
+
+    #[test]
+    fn less_than_rejects_wrong_result() {
+        // the result is copied to the instance column
+        let circuit = CompareCircuit::<1, 8> { a: 3, b: 4, flip: false };
+        let prover = MockProver::run(9, &circuit, vec![vec![Fp::zero(), Fp::zero()]]).unwrap();
+        assert!(prover.verify().is_err());
+
+        // claiming 4 < 3 needs a first limb of 3 - 4 - 1 = -2, which isn't in the table
+        let circuit = CompareCircuit::<1, 8> { a: 4, b: 3, flip: true };
+        let prover = MockProver::run(9, &circuit, vec![vec![Fp::one(), Fp::one()]]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 0,
+                location: FailureLocation::InRegion { region: (2, "less than").into(), offset: 0 },
+            }])
+        );
+    }
+
+    #[test]
+    fn less_than_checks_the_top_limb() {
+        // 3 < 5 in 4 bits: diff = 5 - 3 - 1 + 16 = 17. Claiming 3 >= 5 needs a limb of 17, which
+        // is in the byte table but not below 2^4
+        let circuit = CompareCircuit::<1, 4> { a: 3, b: 5, flip: true };
+        let prover = MockProver::run(9, &circuit, vec![vec![Fp::zero(), Fp::zero()]]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 1,
+                location: FailureLocation::InRegion { region: (2, "less than").into(), offset: 0 },
+            }])
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "12 bits don't take 1 limbs")]
+    fn less_than_bits_must_take_limbs() {
+        let mut meta = ConstraintSystem::<Fp>::default();
+        let [a, b, lt, limb] = [(); 4].map(|_| meta.advice_column());
+        let table = RangeCheckTable::configure(&mut meta);
+        LessThanChip::configure(&mut meta, a, b, lt, [limb], table, 12);
+    }
+}
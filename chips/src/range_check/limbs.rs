@@ -0,0 +1,91 @@
+// Checks that a value fits in LIMBS bytes by decomposing it into 8-bit limbs, every limb being
+// looked up in a RangeCheckTable of 256 values.
+//     value = l_0 + l_1 * 2^8 + ... + l_{LIMBS-1} * 2^(8 * (LIMBS - 1))
+// LIMBS = 8 checks a u64, LIMBS = 16 a u128.
+//
+//  value | l_0 | l_1 | ... | l_{LIMBS-1} | q_decompose
+// -------------------------------------------------------
+//    v   | l_0 | l_1 | ... | l_{LIMBS-1} |      1
+use halo2_proofs::{
+    plonk::*,
+    circuit::{AssignedCell, Layouter, Value},
+    arithmetic::FieldExt, poly::Rotation,
+};
+
+use super::RangeCheckTable;
+
+pub const LIMB_BITS: usize = 8;
+
+#[derive(Debug, Clone)]
+pub struct LimbsConfig<F: FieldExt, const LIMBS: usize>{
+    pub value: Column<Advice>,
+    pub limbs: [Column<Advice>; LIMBS],
+    pub q_decompose: Selector,
+    pub table: RangeCheckTable<F, 256>,
+}
+
+impl<F: FieldExt, const LIMBS: usize> LimbsConfig<F, LIMBS>{
+    // The table is passed in so several configs can share it, it must be loaded by the circuit
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>, limbs: [Column<Advice>; LIMBS], table: RangeCheckTable<F, 256>) -> Self{
+        // get_lower_128 only sees the first 16 bytes
+        assert!((1..=16).contains(&LIMBS), "at most 128 bits");
+
+        //Toggles the decomposition gate and the lookups of the limbs
+        let q_decompose= meta.complex_selector();
+
+        meta.enable_equality(value);
+
+        meta.create_gate("decompose", |meta|{
+            let q= meta.query_selector(q_decompose);
+            let value= meta.query_advice(value, Rotation::cur());
+            let sum= limbs.iter().enumerate().fold(Expression::Constant(F::zero()), |sum, (i, limb)|{
+                let limb= meta.query_advice(*limb, Rotation::cur());
+                sum + limb * Expression::Constant(F::from_u128(1 << (LIMB_BITS * i)))
+            });
+            Constraints::with_selector(q, [("value is the sum of its limbs", value - sum)])
+        });
+
+        for limb in limbs{
+            meta.lookup(|meta|{
+                let q= meta.query_selector(q_decompose);
+                let limb= meta.query_advice(limb, Rotation::cur());
+                vec![(q * limb, table.value)]
+            });
+        }
+
+        Self { value, limbs, q_decompose, table }
+    }
+
+    // Whether `value` fits in LIMBS bytes, i.e. whether assign can decompose it
+    pub fn fits(value: &F) -> bool{
+        let lower= value.get_lower_128();
+        F::from_u128(lower) == *value && (LIMBS == 16 || lower >> (LIMB_BITS * LIMBS) == 0)
+    }
+
+    // Copies `cell` and decomposes it. A value that doesn't fit in LIMBS bytes has no decomposition,
+    // synthesis fails with Error::Synthesis instead of producing a proof that can't verify.
+    // Error::Synthesis can't say which value it was, callers that need to know check `fits` first.
+    pub fn assign(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<F, F>) -> Result<[AssignedCell<F, F>; LIMBS], Error>{
+        layouter.assign_region(||"decompose", |mut region|{
+            self.q_decompose.enable(&mut region, 0)?;
+
+            let value= cell.copy_advice(||"value", &mut region, self.value, 0)?;
+
+            let mut fits= true;
+            let limbs: Value<[u64; LIMBS]>= value.value().map(|v|{
+                fits= Self::fits(v);
+                let lower= v.get_lower_128();
+                std::array::from_fn(|i| ((lower >> (LIMB_BITS * i)) & 0xff) as u64)
+            });
+            if !fits{
+                return Err(Error::Synthesis);
+            }
+
+            let mut cells= Vec::with_capacity(LIMBS);
+            for i in 0..LIMBS{
+                cells.push(region.assign_advice(||format!("limb {}", i), self.limbs[i], 0, ||limbs.map(|l| F::from(l[i])))?);
+            }
+            Ok(cells.try_into().unwrap())
+        })
+    }
+}
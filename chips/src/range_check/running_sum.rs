@@ -0,0 +1,255 @@
+// Checks that a value fits in num_bits bits, for any num_bits up to F::CAPACITY (254 for the pasta
+// fields), with K-bit windows looked up in a RangeCheckTable of RANGE = 2^K values. The value is
+// consumed by a running sum:
+//     z_0 = v,  z_{i+1} = (z_i - k_i) / 2^K,  so  k_i = z_i - 2^K * z_{i+1}
+// Each of the W = num_bits / K windows k_i is looked up. What is left, z_W, must fit in the
+// r = num_bits % K bits of the final short window: z_W and z_W * 2^(K-r) are both looked up,
+// and the second one is only below 2^K if z_W < 2^r. With r = 0 that forces z_W = 0.
+//
+//    z   | shift     | q_window | q_short
+// -----------------------------------------
+//   z_0  |           |    1     |    0
+//   z_1  |           |    1     |    0
+//   ...  |           |   ...    |   ...
+//   z_W  | 2^(K-r)   |    0     |    1
+use halo2_proofs::{
+    plonk::*,
+    circuit::{AssignedCell, Layouter, Value},
+    arithmetic::FieldExt, poly::Rotation,
+};
+
+use super::RangeCheckTable;
+
+#[derive(Debug, Clone)]
+pub struct RunningSumConfig<F: FieldExt, const RANGE: usize>{
+    pub z: Column<Advice>,
+    pub shift: Column<Fixed>,
+    pub q_window: Selector,
+    pub q_short: Selector,
+    pub table: RangeCheckTable<F, RANGE>,
+}
+
+impl<F: FieldExt, const RANGE: usize> RunningSumConfig<F, RANGE>{
+    // the bits of a window
+    pub const K: usize = RANGE.trailing_zeros() as usize;
+
+    // The table is passed in so several configs can share it, it must be loaded by the circuit
+    pub fn configure(meta: &mut ConstraintSystem<F>, z: Column<Advice>, shift: Column<Fixed>, table: RangeCheckTable<F, RANGE>) -> Self{
+        assert!(RANGE.is_power_of_two() && RANGE > 1, "the windows are K bits, the table must hold 2^K values");
+
+        let q_window= meta.complex_selector();
+        let q_short= meta.complex_selector();
+
+        meta.enable_equality(z);
+
+        // k_i = z_i - 2^K * z_{i+1} is in the table
+        meta.lookup(|meta|{
+            let q= meta.query_selector(q_window);
+            let z_cur= meta.query_advice(z, Rotation::cur());
+            let z_next= meta.query_advice(z, Rotation::next());
+            let window= z_cur - z_next * Expression::Constant(F::from(RANGE as u64));
+            vec![(q * window, table.value)]
+        });
+
+        // z_W and z_W * 2^(K-r) are in the table
+        meta.lookup(|meta|{
+            let q= meta.query_selector(q_short);
+            let z= meta.query_advice(z, Rotation::cur());
+            vec![(q * z, table.value)]
+        });
+        meta.lookup(|meta|{
+            let q= meta.query_selector(q_short);
+            let z= meta.query_advice(z, Rotation::cur());
+            let shift= meta.query_fixed(shift, Rotation::cur());
+            vec![(q * z * shift, table.value)]
+        });
+
+        Self { z, shift, q_window, q_short, table }
+    }
+
+    // Copies `cell` and decomposes it, returning the running sum z_0 (the copy), ..., z_W.
+    // A value that doesn't fit in num_bits has no decomposition, synthesis fails with
+    // Error::Synthesis instead of producing a proof that can't verify.
+    pub fn assign(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, num_bits: usize) -> Result<Vec<AssignedCell<F, F>>, Error>{
+        // the value must stay below the modulus for the decomposition to be unique
+        assert!(num_bits <= F::CAPACITY as usize, "at most {} bits", F::CAPACITY);
+        let windows= num_bits / Self::K;
+        let short_bits= num_bits % Self::K;
+
+        layouter.assign_region(||"running sum", |mut region|{
+            let mut z= cell.copy_advice(||"z_0", &mut region, self.z, 0)?;
+            let mut zs= vec![z.clone()];
+
+            let two_pow_k_inv= F::from(RANGE as u64).invert().unwrap();
+            for i in 0..windows{
+                self.q_window.enable(&mut region, i)?;
+                let next= z.value().map(|z| (*z - F::from(lowest_bits(z, Self::K))) * two_pow_k_inv);
+                z= region.assign_advice(||format!("z_{}", i + 1), self.z, i + 1, ||next)?;
+                zs.push(z.clone());
+            }
+
+            self.q_short.enable(&mut region, windows)?;
+            region.assign_fixed(||"shift", self.shift, windows, ||Value::known(F::from(1 << (Self::K - short_bits))))?;
+
+            let mut fits= true;
+            z.value().map(|z| fits= *z == F::from(lowest_bits(z, short_bits)));
+            if !fits{
+                return Err(Error::Synthesis);
+            }
+            Ok(zs)
+        })
+    }
+}
+
+// The value of the lowest `bits` bits of `value`, at most 64 of them
+fn lowest_bits<F: FieldExt>(value: &F, bits: usize) -> u64{
+    let repr= value.to_repr();
+    (0..bits).fold(0, |acc, i|{
+        let bit= (repr.as_ref()[i / 8] >> (i % 8)) & 1;
+        acc | (bit as u64) << i
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    // Checks that value fits in NUM_BITS bits with 8-bit windows. With `cheat` the prover
+    // witnesses z_1, ..., z_W of its choice instead of the running sum
+    struct RunningSumCircuit<const NUM_BITS: usize> {
+        value: Fp,
+        cheat: Option<Vec<Fp>>,
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestConfig {
+        value: Column<Advice>,
+        running_sum: RunningSumConfig<Fp, 256>,
+    }
+
+    impl<const NUM_BITS: usize> Circuit<Fp> for RunningSumCircuit<NUM_BITS> {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { value: Fp::zero(), cheat: self.cheat.clone() }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> TestConfig {
+            let value = meta.advice_column();
+            meta.enable_equality(value);
+            let z = meta.advice_column();
+            let shift = meta.fixed_column();
+            let table = RangeCheckTable::configure(meta);
+            TestConfig { value, running_sum: RunningSumConfig::configure(meta, z, shift, table) }
+        }
+
+        fn synthesize(&self, config: TestConfig, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let rs = &config.running_sum;
+            rs.table.load(&mut layouter)?;
+            let value = layouter.assign_region(|| "value", |mut region| {
+                region.assign_advice(|| "value", config.value, 0, || Value::known(self.value))
+            })?;
+
+            let cheat = match &self.cheat {
+                Some(cheat) => cheat,
+                None => {
+                    let zs = rs.assign(layouter.namespace(|| "range check"), &value, NUM_BITS)?;
+                    assert_eq!(zs.len(), NUM_BITS / 8 + 1);
+                    return Ok(());
+                }
+            };
+            let windows = NUM_BITS / 8;
+            assert_eq!(cheat.len(), windows);
+            layouter.assign_region(|| "running sum", |mut region| {
+                value.copy_advice(|| "z_0", &mut region, rs.z, 0)?;
+                for (i, z) in cheat.iter().enumerate() {
+                    rs.q_window.enable(&mut region, i)?;
+                    region.assign_advice(|| "z", rs.z, i + 1, || Value::known(*z))?;
+                }
+                rs.q_short.enable(&mut region, windows)?;
+                region.assign_fixed(|| "shift", rs.shift, windows, || Value::known(Fp::from(1 << (8 - NUM_BITS % 8))))?;
+                Ok(())
+            })
+        }
+    }
+
+    fn check<const NUM_BITS: usize>(value: Fp) -> Result<Result<(), Vec<VerifyFailure>>, Error> {
+        let circuit = RunningSumCircuit::<NUM_BITS> { value, cheat: None };
+        MockProver::run(9, &circuit, vec![]).map(|prover| prover.verify())
+    }
+
+    #[test]
+    fn running_sum_boundaries() {
+        let two_pow = |bits: u32| Fp::from(2).pow(&[bits as u64, 0, 0, 0]);
+
+        // whole windows only, and a final short window of 4 and 1 bits
+        assert_eq!(check::<64>(Fp::from(u64::MAX)).unwrap(), Ok(()));
+        assert_eq!(check::<12>(Fp::from(4095)).unwrap(), Ok(()));
+        assert_eq!(check::<12>(Fp::zero()).unwrap(), Ok(()));
+        assert_eq!(check::<33>(two_pow(33) - Fp::one()).unwrap(), Ok(()));
+        assert_eq!(check::<4>(Fp::from(15)).unwrap(), Ok(()));
+
+        // bit widths a single table lookup can't reach
+        assert_eq!(check::<128>(two_pow(128) - Fp::one()).unwrap(), Ok(()));
+        assert_eq!(check::<250>(two_pow(250) - Fp::one()).unwrap(), Ok(()));
+
+        // one more than the largest value
+        assert!(matches!(check::<64>(two_pow(64)), Err(Error::Synthesis)));
+        assert!(matches!(check::<12>(Fp::from(4096)), Err(Error::Synthesis)));
+        assert!(matches!(check::<4>(Fp::from(16)), Err(Error::Synthesis)));
+        assert!(matches!(check::<250>(two_pow(250)), Err(Error::Synthesis)));
+        assert!(matches!(check::<64>(-Fp::one()), Err(Error::Synthesis)));
+
+        // the widest check, 2^254 is still below the modulus
+        assert_eq!(check::<254>(two_pow(254) - Fp::one()).unwrap(), Ok(()));
+        assert!(matches!(check::<254>(two_pow(254)), Err(Error::Synthesis)));
+    }
+
+    #[test]
+    #[should_panic(expected = "at most 254 bits")]
+    fn running_sum_rejects_more_bits_than_the_capacity() {
+        let _ = check::<255>(Fp::zero());
+    }
+
+    #[test]
+    fn running_sum_rejects_unconsumed_value() {
+        // 300 in a 12-bit check with z_1 = 0, the window 300 - 256 * 0 isn't in the table
+        let circuit = RunningSumCircuit::<12> { value: Fp::from(300), cheat: Some(vec![Fp::zero()]) };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 0,
+                location: FailureLocation::InRegion { region: (2, "running sum").into(), offset: 0 },
+            }])
+        );
+
+        // 16 in a 4-bit check, z_0 = 16 is in the table but not 16 * 2^4
+        let circuit = RunningSumCircuit::<4> { value: Fp::from(16), cheat: Some(vec![]) };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 2,
+                location: FailureLocation::InRegion { region: (2, "running sum").into(), offset: 0 },
+            }])
+        );
+
+        // 2^16 in a 16-bit check, both windows are zero but z_2 = 1 is left over
+        let circuit = RunningSumCircuit::<16> { value: Fp::from(1 << 16), cheat: Some(vec![Fp::from(256), Fp::one()]) };
+        let prover = MockProver::run(9, &circuit, vec![]).unwrap();
+        assert_eq!(
+            prover.verify(),
+            Err(vec![VerifyFailure::Lookup {
+                lookup_index: 2,
+                location: FailureLocation::InRegion { region: (2, "running sum").into(), offset: 2 },
+            }])
+        );
+    }
+}
@@ -20,10 +20,8 @@ impl<F: FieldExt, const RANGE: usize> RangeCheckTable<F, RANGE>{
         // a special API for lookup table
         //it is like assign region except like bespoke and only works for tables(it is about making lookup tables safe)
         layouter.assign_table(||"load ranhe-check table", |mut table|{
-            let mut offset= 0;
-            for i in 0..RANGE{
+            for (offset, i) in (0..RANGE).enumerate(){
                 table.assign_cell(||"assign cell", self.value, offset, ||Value::known(F::from(i as u64)))?;
-                offset+= 1;
             }
             Ok(())
         })
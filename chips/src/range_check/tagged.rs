@@ -0,0 +1,164 @@
+// One lookup table for every small range of the circuit. Each row is a pair (num_bits, value)
+// and the table holds every value below 2^num_bits for every num_bits up to MAX_BITS:
+//     (0, 0), (1, 0), (1, 1), (2, 0), ..., (2, 3), ..., (MAX_BITS, 2^MAX_BITS - 1)
+// so looking up (bits, v) checks v < 2^bits, whatever bits is. That's 2^(MAX_BITS + 1) - 1 rows.
+// A row where the lookup selector is off looks up (0, 0), which is in the table.
+//
+//  value | num_bits | q_lookup       table:  num_bits | value
+// ------------------------------             -------------------
+//    v   |    n     |    1                       n     |   v
+use std::marker::PhantomData;
+use halo2_proofs::{
+    plonk::*,
+    circuit::{AssignedCell, Layouter, Value},
+    arithmetic::FieldExt, poly::Rotation,
+};
+
+#[derive(Debug, Clone)]
+pub struct TaggedRangeCheckTable<F: FieldExt, const MAX_BITS: usize>{
+    pub num_bits: TableColumn,
+    pub value: TableColumn,
+    _marker: PhantomData<F>
+}
+
+impl<F: FieldExt, const MAX_BITS: usize> TaggedRangeCheckTable<F, MAX_BITS>{
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> Self {
+        let num_bits= meta.lookup_table_column();
+        let value= meta.lookup_table_column();
+        Self { num_bits, value, _marker: PhantomData }
+    }
+
+    // Adds the lookup of the (bits, value) pair returned by `pair`, both are multiplied by
+    // q_lookup. Several configs can look up the same table this way.
+    pub fn lookup(&self, meta: &mut ConstraintSystem<F>, q_lookup: Selector, pair: impl FnOnce(&mut VirtualCells<'_, F>) -> (Expression<F>, Expression<F>)){
+        meta.lookup(|meta|{
+            let q= meta.query_selector(q_lookup);
+            let (bits, value)= pair(meta);
+            vec![(q.clone() * bits, self.num_bits), (q * value, self.value)]
+        });
+    }
+
+    pub fn load(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error>{
+        layouter.assign_table(||"load tagged range-check table", |mut table|{
+            let mut offset= 0;
+            for bits in 0..=MAX_BITS{
+                for value in 0..(1u64 << bits){
+                    table.assign_cell(||"num_bits", self.num_bits, offset, ||Value::known(F::from(bits as u64)))?;
+                    table.assign_cell(||"value", self.value, offset, ||Value::known(F::from(value)))?;
+                    offset += 1;
+                }
+            }
+            Ok(())
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TaggedRangeCheckConfig<F: FieldExt, const MAX_BITS: usize>{
+    pub value: Column<Advice>,
+    pub num_bits: Column<Fixed>,
+    pub q_lookup: Selector,
+    pub table: TaggedRangeCheckTable<F, MAX_BITS>,
+}
+
+impl<F: FieldExt, const MAX_BITS: usize> TaggedRangeCheckConfig<F, MAX_BITS>{
+    // The table is passed in so several configs can share it, it must be loaded by the circuit
+    pub fn configure(meta: &mut ConstraintSystem<F>, value: Column<Advice>, num_bits: Column<Fixed>, table: TaggedRangeCheckTable<F, MAX_BITS>) -> Self{
+        //Toggles the lookup argument
+        let q_lookup= meta.complex_selector();
+
+        meta.enable_equality(value);
+
+        table.lookup(meta, q_lookup, |meta|{
+            let num_bits= meta.query_fixed(num_bits, Rotation::cur());
+            let value= meta.query_advice(value, Rotation::cur());
+            (num_bits, value)
+        });
+
+        Self { value, num_bits, q_lookup, table }
+    }
+
+    // Copies `cell` and checks that it is below 2^bits, the bit length is fixed in the circuit.
+    // Returns the copy.
+    pub fn assign(&self, mut layouter: impl Layouter<F>, cell: &AssignedCell<F, F>, bits: usize) -> Result<AssignedCell<F, F>, Error>{
+        assert!(bits <= MAX_BITS, "the table stops at {} bits", MAX_BITS);
+        layouter.assign_region(||"tagged range check", |mut region|{
+            self.q_lookup.enable(&mut region, 0)?;
+            region.assign_fixed(||"num_bits", self.num_bits, 0, ||Value::known(F::from(bits as u64)))?;
+            cell.copy_advice(||"value", &mut region, self.value, 0)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        circuit::SimpleFloorPlanner,
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    // Checks every (bits, value) pair against the same table of up to 10 bits
+    struct TaggedCircuit {
+        checks: Vec<(usize, u64)>,
+    }
+
+    #[derive(Debug, Clone)]
+    struct TestConfig {
+        input: Column<Advice>,
+        tagged: TaggedRangeCheckConfig<Fp, 10>,
+    }
+
+    impl Circuit<Fp> for TaggedCircuit {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { checks: self.checks.clone() }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> TestConfig {
+            let input = meta.advice_column();
+            meta.enable_equality(input);
+            let value = meta.advice_column();
+            let num_bits = meta.fixed_column();
+            let table = TaggedRangeCheckTable::configure(meta);
+            TestConfig { input, tagged: TaggedRangeCheckConfig::configure(meta, value, num_bits, table) }
+        }
+
+        fn synthesize(&self, config: TestConfig, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            config.tagged.table.load(&mut layouter)?;
+            for (bits, value) in &self.checks {
+                let cell = layouter.assign_region(|| "value", |mut region| {
+                    region.assign_advice(|| "value", config.input, 0, || Value::known(Fp::from(*value)))
+                })?;
+                config.tagged.assign(layouter.namespace(|| format!("{} bits", bits)), &cell, *bits)?;
+            }
+            Ok(())
+        }
+    }
+
+    fn check(checks: Vec<(usize, u64)>) -> Result<(), Vec<VerifyFailure>> {
+        MockProver::run(12, &TaggedCircuit { checks }, vec![]).unwrap().verify()
+    }
+
+    #[test]
+    fn tagged_table_serves_every_range() {
+        // 4, 8 and 10 bits with one table
+        assert_eq!(check(vec![(4, 15), (8, 255), (10, 1023), (10, 0), (1, 1), (0, 0)]), Ok(()));
+
+        for bits in [0, 1, 4, 8, 10] {
+            let too_large = 1 << bits;
+            assert_eq!(
+                check(vec![(bits, too_large - 1), (bits, too_large)]),
+                Err(vec![VerifyFailure::Lookup {
+                    lookup_index: 0,
+                    location: FailureLocation::InRegion { region: (4, "tagged range check").into(), offset: 0 },
+                }]),
+                "{} bits",
+                bits
+            );
+        }
+    }
+}
@@ -0,0 +1,90 @@
+// Pictures of a circuit, behind the dev-graph feature: its layout (which cells of which columns
+// the regions fill) as a PNG or SVG, and its gates as a Graphviz dot graph.
+use std::{error::Error, path::Path};
+
+use halo2_proofs::{
+    dev::{circuit_dot_graph, CircuitLayout},
+    pasta::Fp,
+    plonk::Circuit,
+};
+use plotters::prelude::*;
+
+// The kind of file to write, from the extension of its path
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Png,
+    Svg,
+    Dot,
+}
+
+impl Format {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "png" => Some(Format::Png),
+            "svg" => Some(Format::Svg),
+            "dot" => Some(Format::Dot),
+            _ => None,
+        }
+    }
+}
+
+// Writes the layout of `circuit` on 2^k rows, or its dot graph, to `path`
+pub fn render<C: Circuit<Fp>>(title: &str, k: u32, circuit: &C, path: &Path) -> Result<(), Box<dyn Error>> {
+    match Format::from_path(path).ok_or("the file must end in .png, .svg or .dot")? {
+        Format::Png => draw(title, k, circuit, BitMapBackend::new(path, (1024, 3096)).into_drawing_area()),
+        Format::Svg => draw(title, k, circuit, SVGBackend::new(path, (1024, 3096)).into_drawing_area()),
+        Format::Dot => Ok(std::fs::write(path, circuit_dot_graph::<Fp, C>(circuit))?),
+    }
+}
+
+fn draw<C: Circuit<Fp>, DB: DrawingBackend>(
+    title: &str,
+    k: u32,
+    circuit: &C,
+    root: DrawingArea<DB, plotters::coord::Shift>,
+) -> Result<(), Box<dyn Error>>
+where
+    DB::ErrorType: 'static,
+{
+    root.fill(&WHITE)?;
+    let root = root.titled(title, ("sans-serif", 60))?;
+    CircuitLayout::default()
+        .show_labels(true)
+        .mark_equality_cells(true)
+        .show_equality_constraints(true)
+        .render(k, circuit, &root)?;
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fibonacci::FibonacciCircuit;
+
+    #[test]
+    fn format_from_extension() {
+        assert_eq!(Format::from_path(Path::new("fib.png")), Some(Format::Png));
+        assert_eq!(Format::from_path(Path::new("out/fib.svg")), Some(Format::Svg));
+        assert_eq!(Format::from_path(Path::new("fib.dot")), Some(Format::Dot));
+        assert_eq!(Format::from_path(Path::new("fib.jpg")), None);
+        assert_eq!(Format::from_path(Path::new("fib")), None);
+    }
+
+    #[test]
+    fn render_every_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        let circuit = FibonacciCircuit::<Fp>::new(9);
+
+        for file in ["fib.png", "fib.svg", "fib.dot"] {
+            let path = dir.join(file);
+            render("Fibonacci", circuit.k(), &circuit, &path).unwrap();
+            assert!(std::fs::metadata(&path).unwrap().len() > 0, "{}", file);
+        }
+        let dot = std::fs::read_to_string(dir.join("fib.dot")).unwrap();
+        assert!(dot.starts_with("digraph"));
+
+        assert!(render("Fibonacci", circuit.k(), &circuit, &dir.join("fib.jpg")).is_err());
+    }
+}
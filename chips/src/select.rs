@@ -0,0 +1,169 @@
+// out = if cond {x} else {y}, for a boolean cond.
+//     out = y + cond * (x - y)
+// is x when cond = 1 and y when cond = 0, and cond is constrained to be one of the two.
+//
+//  cond | x | y | out | q_select
+// --------------------------------
+//   c   | x | y | out |    1
+use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation};
+
+#[derive(Clone, Debug)]
+pub struct SelectConfig {
+    pub cond: Column<Advice>,
+    pub x: Column<Advice>,
+    pub y: Column<Advice>,
+    pub out: Column<Advice>,
+    pub q_select: Selector,
+}
+
+pub struct SelectChip<F: FieldExt> {
+    config: SelectConfig,
+    _marker: std::marker::PhantomData<F>,
+}
+
+impl<F: FieldExt> SelectChip<F> {
+    pub fn construct(config: SelectConfig) -> Self {
+        SelectChip { config, _marker: std::marker::PhantomData }
+    }
+
+    // The columns can be shared with other chips, the select rows are regions of their own
+    pub fn configure(
+        meta: &mut ConstraintSystem<F>,
+        cond: Column<Advice>,
+        x: Column<Advice>,
+        y: Column<Advice>,
+        out: Column<Advice>,
+    ) -> SelectConfig {
+        let q_select = meta.selector();
+        for column in [cond, x, y, out] {
+            meta.enable_equality(column);
+        }
+
+        meta.create_gate("select", |meta| {
+            let q = meta.query_selector(q_select);
+            let cond = meta.query_advice(cond, Rotation::cur());
+            let x = meta.query_advice(x, Rotation::cur());
+            let y = meta.query_advice(y, Rotation::cur());
+            let out = meta.query_advice(out, Rotation::cur());
+            Constraints::with_selector(q, [
+                ("cond is a bit", cond.clone() * (Expression::Constant(F::one()) - cond.clone())),
+                ("out is x or y", out - (y.clone() + cond * (x - y))),
+            ])
+        });
+
+        SelectConfig { cond, x, y, out, q_select }
+    }
+
+    // Copies the three cells and returns the selected one
+    pub fn assign(
+        &self,
+        mut layouter: impl Layouter<F>,
+        cond: &AssignedCell<F, F>,
+        x: &AssignedCell<F, F>,
+        y: &AssignedCell<F, F>,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "select",
+            |mut region| {
+                config.q_select.enable(&mut region, 0)?;
+                let cond = cond.copy_advice(|| "cond", &mut region, config.cond, 0)?;
+                let x = x.copy_advice(|| "x", &mut region, config.x, 0)?;
+                let y = y.copy_advice(|| "y", &mut region, config.y, 0)?;
+
+                let out = cond.value().zip(x.value()).zip(y.value()).map(|((cond, x), y)| {
+                    if *cond == F::one() {
+                        *x
+                    } else {
+                        *y
+                    }
+                });
+                region.assign_advice(|| "out", config.out, 0, || out)
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::{
+        dev::{FailureLocation, MockProver, VerifyFailure},
+        pasta::Fp,
+    };
+
+    #[derive(Clone, Debug)]
+    struct TestConfig {
+        input: Column<Advice>,
+        select: SelectConfig,
+        instance: Column<Instance>,
+    }
+
+    // Exposes if cond {x} else {y}
+    struct SelectCircuit {
+        cond: u64,
+        x: u64,
+        y: u64,
+    }
+
+    impl Circuit<Fp> for SelectCircuit {
+        type Config = TestConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self { cond: 0, x: 0, y: 0 }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<Fp>) -> TestConfig {
+            let input = meta.advice_column();
+            let [cond, x, y, out] = [(); 4].map(|_| meta.advice_column());
+            let instance = meta.instance_column();
+            meta.enable_equality(input);
+            meta.enable_equality(instance);
+
+            let select = SelectChip::configure(meta, cond, x, y, out);
+            TestConfig { input, select, instance }
+        }
+
+        fn synthesize(&self, config: TestConfig, mut layouter: impl Layouter<Fp>) -> Result<(), Error> {
+            let [cond, x, y] = layouter.assign_region(|| "inputs", |mut region| {
+                let mut cells = vec![];
+                for (row, value) in [self.cond, self.x, self.y].into_iter().enumerate() {
+                    cells.push(region.assign_advice(|| "input", config.input, row, || Value::known(Fp::from(value)))?);
+                }
+                Ok::<_, Error>(cells.try_into().unwrap())
+            })?;
+
+            let out = SelectChip::construct(config.select).assign(layouter.namespace(|| "select"), &cond, &x, &y)?;
+            layouter.constrain_instance(out.cell(), config.instance, 0)
+        }
+    }
+
+    fn select(cond: u64, x: u64, y: u64, out: u64) -> Result<(), Vec<VerifyFailure>> {
+        let circuit = SelectCircuit { cond, x, y };
+        MockProver::run(4, &circuit, vec![vec![Fp::from(out)]]).unwrap().verify()
+    }
+
+    #[test]
+    fn select_picks_x_or_y() {
+        assert_eq!(select(1, 7, 9, 7), Ok(()));
+        assert_eq!(select(0, 7, 9, 9), Ok(()));
+        assert_eq!(select(1, 7, 7, 7), Ok(()));
+
+        assert!(select(1, 7, 9, 9).is_err());
+        assert!(select(0, 7, 9, 7).is_err());
+    }
+
+    #[test]
+    fn select_rejects_non_boolean_cond() {
+        // with x = y any cond gives out = y, only the bit constraint can catch cond = 2
+        assert_eq!(
+            select(2, 7, 7, 7),
+            Err(vec![VerifyFailure::ConstraintNotSatisfied {
+                constraint: ((0, "select").into(), 0, "cond is a bit").into(),
+                location: FailureLocation::InRegion { region: (1, "select").into(), offset: 0 },
+                cell_values: vec![(((Any::Advice, 1).into(), 0).into(), "0x2".to_string())],
+            }])
+        );
+    }
+}
@@ -1,78 +1,123 @@
-use std::marker::PhantomData;
-
+//Difference: the chip owns its columns, configure creates them, so no other chip can reuse them
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::*,
-    plonk::*, poly::Rotation,
+    plonk::*,
+    pasta::Fp, dev::MockProver,
 };
+use chips::{fibonacci::{FiboChip, FiboCircuit, FiboConfig}, prover};
 
-#[derive(Debug, Clone)]
-struct FiboConfig{
-    pub advice: [Column<Advice>; 3],
-    pub selector: Selector,
-} 
-
-struct FiboChip<F: FieldExt>{
-    config: FiboConfig,
-    _marker: PhantomData<F>,
-}
+// FiboCircuit of chips/src/fibonacci/three_column.rs, configured with FiboChip::configure_own_columns.
+// The instance column is [a, b, F[n]]
+struct MyCircuit<F>(FiboCircuit<F>);
 
-impl<F:FieldExt> FiboChip<F>  {
-    fn construct(config: FiboConfig) -> Self {
-        Self { config, _marker: PhantomData}
+impl<F:FieldExt> MyCircuit<F> {
+    fn new(a: Value<F>, b: Value<F>, n: usize) -> Self {
+        Self(FiboCircuit::new(a, b, n))
     }
 
-    fn configure(meta: &mut ConstraintSystem<F>) -> FiboConfig {
-        let col_a= meta.advice_column();
-        let col_b= meta.advice_column();
-        let col_c= meta.advice_column();
-        let selector: Selector= meta.selector();
-
-        meta.enable_equality(col_a);
-        meta.enable_equality(col_b);
-        meta.enable_equality(col_c);
-
-
-        meta.create_gate("add", |meta|{
-            //this expression will usually correspond to a cell like a relative cell inside a custom gate
-            let s= meta.query_selector(selector);
-            let a= meta.query_advice(col_a, Rotation::cur());
-            let b= meta.query_advice(col_b, Rotation::cur());
-            let c= meta.query_advice(col_c, Rotation::cur());
-            //Rotation::next(): you query the next row, relative next row for this cell
-            //With Rotation, we can define an offset like 5, 20, -100, etc. It is relative to the row.
-            vec![s*(a + b - c)] // means s * ( a + b - c) == 0
-        });
-        FiboConfig { 
-            advice: [col_a, col_b, col_c ], 
-            selector, 
-        }
+    //the smallest circuit size that fits all the rows
+    fn k(&self) -> u32 {
+        prover::min_k::<F, Self>(self.0.rows())
     }
-}
 
-#[derive(Default)]
-struct MyCircuit<F>{
-    pub a: Option<F>,
-    pub b: Option<F>,
+    //[a, b, F[n]], the values the instance column must hold
+    fn public_input(&self, a: F, b: F) -> Vec<F> {
+        self.0.public_input(a, b)
+    }
 }
 
-/*impl<F:FieldExt> Circuit<F> for MyCircuit<F> {
+impl<F:FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = FiboConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self(self.0.without_witnesses())
     }
 
+    //no columns to create here, the chip does it
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        FiboChip::configure(meta)
+        FiboChip::configure_own_columns(meta)
     }
 
     fn synthesize(&self, config: Self::Config, layouter: impl Layouter<F>) -> Result<(), Error> {
-        
+        self.0.synthesize(config, layouter)
     }
-}*/
+}
 
 fn main(){
+    //instantiate a circuit, `cargo run --bin appraoch1 -- 50` proves F[50]
+    let n= std::env::args().nth(1).map(|n| n.parse().expect("n must be a number")).unwrap_or(9);
+
+    let a= Fp::from(1);
+    let b= Fp::from(1);
+
+    let circuit= MyCircuit::new(Value::known(a), Value::known(b), n);
+    let k= circuit.k();//the size of the circuit
+    let public_input= circuit.public_input(a, b);
+
+    let prover= MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
+    prover.assert_satisfied();
+
+    //the mock prover only checks the constraints, now we generate and verify a real proof
+    let report= prover::prove_and_verify(k, circuit, &[&public_input]).unwrap();
+    println!("{}", report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use halo2_proofs::dev::{FailureLocation, VerifyFailure};
 
-}
\ No newline at end of file
+    #[test]
+    fn fibonacci_example1_real_proof() {
+        let a= Fp::from(1);
+        let b= Fp::from(1);
+        let out= Fp::from(55);
+
+        let circuit= MyCircuit::new(Value::known(a), Value::known(b), 9);
+        let k= circuit.k();
+        assert_eq!(k, 4);
+        assert_eq!(circuit.public_input(a, b), vec![a, b, out]);
+
+        let report= prover::prove_and_verify(k, circuit, &[&[a, b, out]]).unwrap();
+        assert!(report.proof_size > 0);
+
+        // The proof is still created with a wrong output, but it must not verify
+        let circuit= MyCircuit::new(Value::known(a), Value::known(b), 9);
+        assert!(prover::prove_and_verify(k, circuit, &[&[a, b, out + Fp::one()]]).is_err());
+    }
+
+    #[test]
+    fn fibonacci_example1_k_grows_with_n() {
+        for n in [2, 9, 50, 200] {
+            let circuit= MyCircuit::new(Value::known(Fp::from(1)), Value::known(Fp::from(1)), n);
+            let k= circuit.k();
+            let public_input= circuit.public_input(Fp::from(1), Fp::from(1));
+            MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap().assert_satisfied();
+
+            // one size smaller doesn't have enough rows
+            assert!(MockProver::run(k - 1, &circuit, vec![public_input]).is_err());
+        }
+    }
+
+    #[test]
+    fn fibonacci_example1_rejects_tampered_output() {
+        let circuit= MyCircuit::new(Value::known(Fp::from(1)), Value::known(Fp::from(1)), 9);
+        let mut public_input= circuit.public_input(Fp::from(1), Fp::from(1));
+        public_input[2] += Fp::one();
+
+        // the c cell of the last "next row" region and the output row of the instance column
+        let prover= MockProver::run(circuit.k(), &circuit, vec![public_input]).unwrap();
+        assert_eq!(prover.verify(), Err(vec![
+            VerifyFailure::Permutation {
+                column: (Any::Advice, 2).into(),
+                location: FailureLocation::InRegion { region: (7, "next row").into(), offset: 0 },
+            },
+            VerifyFailure::Permutation {
+                column: (Any::Instance, 0).into(),
+                location: FailureLocation::OutsideRegion { row: 2 },
+            },
+        ]));
+    }
+}
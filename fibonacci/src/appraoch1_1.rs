@@ -1,130 +1,53 @@
 //Difference: if you want two chips to reuse the same columns, you have to manually specift them
-use std::marker::PhantomData;
-
 use halo2_proofs::{
     arithmetic::FieldExt,
     circuit::*,
-    plonk::*, poly::Rotation,
+    plonk::*,
     pasta::Fp, dev::MockProver,
 };
+use chips::{fibonacci::{FiboChip, FiboConfig}, prover};
 
-#[derive(Debug, Clone)]
-struct ACell<F: FieldExt>(AssignedCell<F, F>);
-
-#[derive(Debug, Clone)]
-struct FiboConfig{
-    pub advice: [Column<Advice>; 3],
-    pub selector: Selector,
-} 
+// The three column chip of chips/src/fibonacci/three_column.rs, without exposing anything: the instance
+// column stays empty and the proof only says that some sequence was computed
 
-struct FiboChip<F: FieldExt>{
-    config: FiboConfig,
-    _marker: PhantomData<F>,
+// Proves the sequence F[0]= a, F[1]= b, ..., F[n] with F[i]= F[i-2] + F[i-1]
+struct MyCircuit<F>{
+    pub a: Value<F>,
+    pub b: Value<F>,
+    pub n: usize,
 }
 
-impl<F:FieldExt> FiboChip<F>  {
-    fn construct(config: FiboConfig) -> Self {
-        Self { config, _marker: PhantomData}
-    }
-
-    fn configure(meta: &mut ConstraintSystem<F>, advice: [Column<Advice>; 3]) -> FiboConfig {
-   
-        let col_a= advice[0];
-        let col_b= advice[1];
-        let col_c= advice[2];
-        let selector: Selector= meta.selector();
-
-        meta.enable_equality(col_a);
-        meta.enable_equality(col_b);
-        meta.enable_equality(col_c);
-
-
-        meta.create_gate("add", |meta|{
-            //this expression will usually correspond to a cell like a relative cell inside a custom gate
-            let s= meta.query_selector(selector);
-            let a= meta.query_advice(col_a, Rotation::cur());
-            let b= meta.query_advice(col_b, Rotation::cur());
-            let c= meta.query_advice(col_c, Rotation::cur());
-            //Rotation::next(): you query the next row, relative next row for this cell
-            //With Rotation, we can define an offset like 5, 20, -100, etc. It is relative to the row.
-            vec![s*(a + b - c)] // means s * ( a + b - c) == 0
-        });
-        FiboConfig { 
-            advice: [col_a, col_b, col_c ], 
-            selector, 
-        }
+impl<F:FieldExt> MyCircuit<F> {
+    fn new(a: Value<F>, b: Value<F>, n: usize) -> Self {
+        assert!(n >= 2, "the first row already computes F[2]");
+        Self { a, b, n }
     }
 
-    fn assign_first_row(&self, mut layouter: impl Layouter<F>, a: Option<F>, b: Option<F>) -> Result
-    <(ACell<F>, ACell<F>, ACell<F>), Error>{
-        layouter.assign_region(||"first row", |mut region|{
-            self.config.selector.enable(&mut region, 0)?;
-
-            let a_cell= region.assign_advice(
-                || "a",
-                self.config.advice[0],
-                0,
-                || a.ok_or(Error::Synthesis),
-            ).map(ACell)?;
-
-            let b_cell= region.assign_advice(
-                || "b",
-                self.config.advice[1],
-                0,
-                || b.ok_or(Error::Synthesis),
-            ).map(ACell)?;
-
-            let c_val= a.and_then(|a| b.map(|b| a + b));
-
-            let c_cell= region.assign_advice(
-                || "c",
-                self.config.advice[2],
-                0,
-                || c_val.ok_or(Error::Synthesis),
-            ).map(ACell)?;
-
-            Ok((a_cell, b_cell, c_cell))
-
-        })
+    //one row per term after the seeds
+    fn rows(&self) -> usize {
+        self.n - 1
     }
 
-    fn assign_row(&self, mut layouter: impl Layouter<F>, prev_b: &ACell<F>, prev_c: &ACell<F>) -> Result<ACell<F>, Error> {
-        layouter.assign_region(||"next row", |mut region|{
-            self.config.selector.enable(&mut region, 0)?;//enable the selector to turn on the custom gate
-
-            prev_b.0.copy_advice(||"a", &mut region, self.config.advice[0], 0)?;
-            //prev_b.0.copy_advice(||"a", &mut region: current region, self.config.advice[0]: the first advice column inside our config(row), 0: offset like the current row, the first row in the region)?; a description of the description previous line
-            prev_c.0.copy_advice(||"b", &mut region, self.config.advice[1], 0)?;
-
-            let c_val= prev_b.0.value().and_then(|b| {
-                prev_c.0.value().map(|c| *b + *c)
-            });
-
-            let c_cell= region.assign_advice(||"c", self.config.advice[2], 0, ||c_val.ok_or(Error::Synthesis)).map(ACell)?;
-            Ok(c_cell)
-        })
+    //the smallest circuit size that fits all the rows
+    fn k(&self) -> u32 {
+        prover::min_k::<F, Self>(self.rows())
     }
 }
 
-#[derive(Default)]
-struct MyCircuit<F>{
-    pub a: Option<F>,
-    pub b: Option<F>,
-}
-
 impl<F:FieldExt> Circuit<F> for MyCircuit<F> {
     type Config = FiboConfig;
     type FloorPlanner = SimpleFloorPlanner;
 
     fn without_witnesses(&self) -> Self {
-        Self::default()
+        Self { a: Value::unknown(), b: Value::unknown(), n: self.n }
     }
 
     fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
         let col_a= meta.advice_column();
         let col_b= meta.advice_column();
         let col_c= meta.advice_column();
-        FiboChip::configure(meta, [col_a, col_b, col_c])
+        let instance= meta.instance_column();
+        FiboChip::configure(meta, [col_a, col_b, col_c], instance)
     }
 
     fn synthesize(&self, config: Self::Config, mut layouter: impl Layouter<F>) -> Result<(), Error> {
@@ -132,7 +55,7 @@ impl<F:FieldExt> Circuit<F> for MyCircuit<F> {
 
         let (_, mut prev_b, mut prev_c)= chip.assign_first_row(layouter.namespace(||"first row"), self.a, self.b)?;
         
-        for _i in 3..10 {
+        for _i in 3..=self.n {
             let c_cell= chip. assign_row(layouter.namespace(||"next row"), &prev_b, &prev_c)?;
             prev_b= prev_c;
             prev_c= c_cell;
@@ -143,18 +66,46 @@ impl<F:FieldExt> Circuit<F> for MyCircuit<F> {
 }
 
 fn main(){
-    //instantiate a circuit
-
-    let k= 4;//the size of the circuit
+    //instantiate a circuit, `cargo run --bin appraoch1_1 -- 50` proves F[50]
+    let n= std::env::args().nth(1).map(|n| n.parse().expect("n must be a number")).unwrap_or(9);
 
     let a= Fp::from(1);
     let b= Fp::from(1);
 
-    let circuit= MyCircuit{
-        a: Some(a),
-        b: Some(b),
-    };
+    let circuit= MyCircuit::new(Value::known(a), Value::known(b), n);
+    let k= circuit.k();//the size of the circuit
 
-    let prover= MockProver::run(k, &circuit, vec![]).unwrap();
+    let prover= MockProver::run(k, &circuit, vec![vec![]]).unwrap();
     prover.assert_satisfied();
-}
\ No newline at end of file
+
+    //the mock prover only checks the constraints, now we generate and verify a real proof
+    let report= prover::prove_and_verify(k, circuit, &[&[]]).unwrap();
+    println!("{}", report);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fibonacci_example1_1_real_proof() {
+        let circuit= MyCircuit::new(Value::known(Fp::from(1)), Value::known(Fp::from(1)), 9);
+        let k= circuit.k();
+        assert_eq!(k, 4);
+
+        let report= prover::prove_and_verify(k, circuit, &[&[]]).unwrap();
+        assert!(report.proof_size > 0);
+    }
+
+    #[test]
+    fn fibonacci_example1_1_k_grows_with_n() {
+        for n in [2, 9, 50, 200] {
+            let circuit= MyCircuit::new(Value::known(Fp::from(1)), Value::known(Fp::from(1)), n);
+            let k= circuit.k();
+            MockProver::run(k, &circuit, vec![vec![]]).unwrap().assert_satisfied();
+
+            // one size smaller doesn't have enough rows
+            assert!(MockProver::run(k - 1, &circuit, vec![vec![]]).is_err());
+        }
+    }
+}
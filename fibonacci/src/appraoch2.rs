@@ -1,219 +1,117 @@
-use std::marker::PhantomData;
-use halo2_proofs::{arithmetic::FieldExt, circuit::*, plonk::*, poly::Rotation, pasta::Fp, dev::MockProver,};
-
-#[derive(Debug, Clone)]
-struct ACell<F: FieldExt>(AssignedCell<F, F>);
-
-#[derive(Debug, Clone)]
-struct FibonacciConfig {
-    pub advice: Column<Advice>,
-    pub selector: Selector,
-    pub instance: Column<Instance>,
-}
-
-#[derive(Debug, Clone)]
-struct FibonacciChip<F: FieldExt> {
-    config: FibonacciConfig,
-    _marker: PhantomData<F>,
+// The single column Fibonacci chip, see chips/src/fibonacci/single_column.rs
+use std::{error, path::Path};
+use halo2_proofs::{poly::commitment::Params, pasta::Fp, dev::MockProver,};
+
+use chips::{artifact, fibonacci::FibonacciCircuit as MyCircuit, prover};
+
+// Name recorded in the artifact headers, the verifier refuses artifacts made for another circuit
+const CIRCUIT_NAME: &str = "fibonacci-appraoch2";
+
+// Proves F[n] for the given public input and writes params.bin, vk.bin and proof.bin into `dir`
+fn prove_to_dir(dir: &Path, n: usize, public_input: &[Fp]) -> Result<(), Box<dyn error::Error>> {
+    let k = MyCircuit::<Fp>::new(n).k();
+    let params = Params::new(k);
+    let pk = prover::keygen(&params, &MyCircuit::new(n))?;
+    let proof = prover::prove(&params, &pk, MyCircuit::new(n), &[public_input])?;
+
+    artifact::write_params(dir.join("params.bin"), CIRCUIT_NAME, k, &params, pk.get_vk())?;
+    artifact::write_vk(dir.join("vk.bin"), CIRCUIT_NAME, k, pk.get_vk())?;
+    artifact::write_proof(dir.join("proof.bin"), CIRCUIT_NAME, k, pk.get_vk(), &[public_input], &proof)?;
+    Ok(())
 }
 
-impl<F: FieldExt> FibonacciChip<F> {
-    pub fn construct(config: FibonacciConfig) -> Self {
-        Self {
-            config,
-            _marker: PhantomData,
+// Checks the proof of F[n] in `dir` using only the artifacts and the shape of the circuit: no
+// witness is assigned, the verifying key is regenerated and compared with the recorded digest.
+// Returns the public inputs the proof was verified against.
+fn verify_from_dir(dir: &Path, n: usize) -> Result<Vec<Vec<Fp>>, Box<dyn error::Error>> {
+    let (params_header, params) = artifact::read_params(dir.join("params.bin"))?;
+    let (vk_header, vk) = artifact::read_vk(dir.join("vk.bin"), &params, &MyCircuit::<Fp>::new(n))?;
+    let proof = artifact::read_proof(dir.join("proof.bin"))?;
+
+    for header in [&params_header, &vk_header, &proof.header] {
+        if header.circuit != CIRCUIT_NAME || header.k != vk_header.k || header.vk_digest != vk_header.vk_digest {
+            return Err(format!("{:?} artifact was not made for this verifying key", header.kind).into());
         }
     }
 
-    pub fn configure(meta: &mut ConstraintSystem<F>,advice: Column<Advice>, instance: Column<Instance>) -> FibonacciConfig {
-        let selector = meta.selector();
-
-        meta.enable_equality(advice);
-        meta.enable_equality(instance);
-
-        meta.create_gate("add", |meta| {
-            //
-            // advice  | selector
-            //   a    |     s
-            //   b    |
-            //   c    |
-            let s = meta.query_selector(selector);
-            let a = meta.query_advice(advice, Rotation::cur());
-            let b = meta.query_advice(advice, Rotation::next());
-            let c = meta.query_advice(advice, Rotation(2));
-            vec![s * (a + b - c)]
-        });
-
-        FibonacciConfig {
-            advice,
-            selector,
-            instance,
-        }
-    }
-
-    fn assign(&self, mut layouter: impl Layouter<F>, nrows:usize) -> Result
-    <AssignedCell<F, F>, Error>{
-        layouter.assign_region(
-            || "entire fibonacci table",
-            |mut region| {
-                self.config.selector.enable(&mut region, 0)?;
-                self.config.selector.enable(&mut region, 1)?;
-
-                let mut a_cell= region.assign_advice_from_instance(||"1", self.config.instance, 0, self.config.advice, 0)?;
-                let mut b_cell= region.assign_advice_from_instance(||"2", self.config.instance, 1, self.config.advice, 1)?;
-
-                for row in 2..nrows{
-                    if row < nrows - 2{
-                        self.config.selector.enable(&mut region, row)?;
-                    }
-
-                    let c_val= a_cell.value().and_then(|a|{
-                        b_cell.value().map(|b| *a + *b)
-                    });
-                    let c_cell= region.assign_advice(||"advice", self.config.advice, row, ||c_val.ok_or(Error::Synthesis))?;
-                    
-                    a_cell= b_cell;
-                    b_cell= c_cell;
-                }
-
-                Ok(b_cell)
-            },
-        )
-    }
-
-    pub fn expose_public(
-        &self,
-        mut layouter: impl Layouter<F>,
-        cell: AssignedCell<F, F>,
-        row: usize,
-    ) -> Result<(), Error> {
-        layouter.constrain_instance(cell.cell(), self.config.instance, row)
-    }
-}
-
-#[derive(Default)]
-struct MyCircuit<F>{
-    pub a: Option<F>,
-    pub b: Option<F>,
+    prover::verify(&params, &vk, &proof.instance_slices(), &proof.proof)?;
+    Ok(proof.instances)
 }
 
-impl<F: FieldExt> Circuit<F> for MyCircuit<F> {
-    type Config = FibonacciConfig;
-    type FloorPlanner = SimpleFloorPlanner;
-
-    fn without_witnesses(&self) -> Self {
-        Self::default()
-    }
-
-    fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
-        let advice= meta.advice_column();
-        let instance= meta.instance_column();
-        FibonacciChip::configure(meta, advice, instance)
-    }
-
-    fn synthesize(
-        &self,
-        config: Self::Config,
-        mut layouter: impl Layouter<F>,
-    ) -> Result<(), Error> {
-        let chip = FibonacciChip::construct(config);
-
-        let out_cell= chip.assign(layouter.namespace(||"entire table"), 10)?;
-
-        chip.expose_public(layouter.namespace(|| "out"), out_cell, 2)?;
-
-        Ok(())
-    }
-}
-
-
 fn main(){
-
-    let k= 4;//the size of the circuit
+    // cargo run --bin appraoch2 -- [n]                  proves F[n], F[9] by default
+    // cargo run --bin appraoch2 -- prove <dir> [n]     writes a proof of F[n] and its artifacts
+    // cargo run --bin appraoch2 -- verify <dir> [n]    checks them without any witness
+    let args: Vec<String> = std::env::args().collect();
+    let parse_n = |arg: Option<&String>| arg.map(|n| n.parse().expect("n must be a number")).unwrap_or(9);
 
     let a = Fp::from(1); // F[0]
     let b = Fp::from(1); // F[1]
-    let out = Fp::from(55); // F[9]
 
-    let circuit= MyCircuit{
-        a: Some(a),
-        b: Some(b),
-    };
-
-    let mut public_input = vec![a, b, out];
+    match (args.get(1).map(String::as_str), args.get(2)) {
+        (Some("prove"), Some(dir)) => {
+            let n = parse_n(args.get(3));
+            prove_to_dir(Path::new(dir), n, &MyCircuit::new(n).public_input(a, b)).unwrap();
+            println!("proof written to {}", dir);
+            return;
+        }
+        (Some("verify"), Some(dir)) => {
+            let instances = verify_from_dir(Path::new(dir), parse_n(args.get(3))).unwrap();
+            println!("proof verified, public inputs: {:?}", instances);
+            return;
+        }
+        _ => {}
+    }
 
-    let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
-    prover.assert_satisfied();
+    let n = parse_n(args.get(1));
+    let circuit= MyCircuit::new(n);
+    let k= circuit.k();//the size of the circuit
 
-    public_input[2] += Fp::one();
-    let _prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
-    _prover.assert_satisfied();
+    let mut public_input = circuit.public_input(a, b);
 
-}
-
-/*mod tests {
-    use super::*;
-    #[cfg(feature = "dev-graph")]
-    #[test]
-    fn print(){
-
-    let k = 4;
-    let a = Fp::from(1);
-    let b = Fp::from(1);
-    let out = Fp::from(55);
-    let circuit = FibonacciCircuit {
-        a:Some(a), b:Some(b)
-    };
-    let mut public_inputs = vec![a, b, out];
-    // This prover is faster and 'fake', but is mostly a devtool for debugging
-    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
-    // This function will pretty-print on errors
+    let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
     prover.assert_satisfied();
-    public_inputs[2] += Fp::one();
-    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
-    // prover.assert_satisfied();
-
-    use plotters::prelude::*;
 
-    let root = BitMapBackend::new("fib-2—layout.png", (1024, 7680)).into_drawing_area();
-    //root.fiti(&WHITE).unwrap();
-    let root1 = root.titled("Fib 2 Layout", ("sans—serif", 60)).unwrap();
-    let circuit:FibonacciCircuit<Fp> = FibonacciCircuit { a: None, b: None };
-    halo2_proofs::dev::CircuitLayout::default()
-        .render(4, &circuit, &root1)
-        .unwrap();
+    //the mock prover only checks the constraints, now we generate and verify a real proof
+    let report = prover::prove_and_verify(k, MyCircuit::new(n), &[&public_input]).unwrap();
+    println!("{}", report);
 
+    //a wrong F[n] breaks the copy constraint between the last term and the instance column
+    public_input[2] += Fp::one();
+    let prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
+    let failures = prover.verify().expect_err("a wrong output must be rejected");
+    for failure in failures {
+        println!("wrong output rejected: {}", failure);
     }
-}*/
+}
 
 #[cfg(test)]
 mod tests {
+    use super::{artifact, prove_to_dir, verify_from_dir};
+    #[cfg(feature = "dev-graph")]
     use super::MyCircuit;
-    use std::marker::PhantomData;
-    use halo2_proofs::{dev::MockProver, pasta::Fp};
+    use halo2_proofs::pasta::Fp;
 
     #[test]
-    fn fibonacci_example2() {
-        let k = 4;
+    fn fibonacci_example2_artifacts() {
+        // removed when it goes out of scope
+        let temp_dir = tempfile::tempdir().unwrap();
+        let dir = temp_dir.path();
 
-        let a = Fp::from(1); // F[0]
-        let b = Fp::from(1); // F[1]
-        let out = Fp::from(55); // F[9]
+        let public_input = [Fp::from(1), Fp::from(1), Fp::from(55)];
+        prove_to_dir(dir, 9, &public_input).unwrap();
+        assert_eq!(verify_from_dir(dir, 9).unwrap(), vec![public_input.to_vec()]);
 
-        let circuit = MyCircuit{
-            a: Some(a),
-            b: Some(b),
-        };
+        // A proof of F[9] is not a proof of F[10]: the regenerated verifying key doesn't match
+        assert!(verify_from_dir(dir, 10).is_err());
 
-        let mut public_input = vec![a, b, out];
+        // A proof file is not a params file
+        assert!(artifact::read_params(dir.join("proof.bin")).is_err());
 
-        let prover = MockProver::run(k, &circuit, vec![public_input.clone()]).unwrap();
-        prover.assert_satisfied();
-
-        public_input[2] += Fp::one();
-        let _prover = MockProver::run(k, &circuit, vec![public_input]).unwrap();
-        // uncomment the following line and the assert will fail
-        // _prover.assert_satisfied();
+        // Swap the proof for one of a different output: the headers still match but the proof doesn't verify
+        let wrong_input = [Fp::from(1), Fp::from(1), Fp::from(56)];
+        prove_to_dir(dir, 9, &wrong_input).unwrap();
+        assert!(verify_from_dir(dir, 9).is_err());
     }
 
     #[cfg(feature = "dev-graph")]
@@ -224,12 +122,9 @@ mod tests {
         root.fill(&WHITE).unwrap();
         let root = root.titled("Fib 2 Layout", ("sans-serif", 60)).unwrap();
 
-        let a = Fp::from(1); // F[0]
-        let b = Fp::from(1); // F[1]
-        
-        let circuit:MyCircuit<Fp> = MyCircuit { a: None, b: None };
+        let circuit:MyCircuit<Fp> = MyCircuit::new(9);
         halo2_proofs::dev::CircuitLayout::default()
-            .render(4, &circuit, &root)
+            .render(circuit.k(), &circuit, &root)
             .unwrap();
     }
-}
\ No newline at end of file
+}
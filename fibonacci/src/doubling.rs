@@ -0,0 +1,35 @@
+// Compares the fast doubling chip with the single column chip, see chips/src/fibonacci/doubling.rs
+use halo2_proofs::{circuit::Value, pasta::Fp};
+
+use chips::{
+    fibonacci::{fibonacci, DoublingCircuit, FibonacciCircuit},
+    prover,
+};
+
+fn main() {
+    // cargo run --release --bin doubling
+    // Compares the rows and proving time of both chips for a few n, each doubling circuit being
+    // sized for the bits of its n
+    println!("{:>6} | {:>15} | {:>15} | {:>15} | {:>15}", "n", "linear rows/k", "linear prove", "doubling rows/k", "doubling prove");
+    for n in [16u64, 64, 256, 1024, 4096] {
+        let out = fibonacci(Fp::zero(), Fp::one(), n as usize);
+
+        let linear = FibonacciCircuit::<Fp>::new(n as usize);
+        let (linear_rows, linear_k) = (linear.rows(), linear.k());
+        let linear_report = prover::prove_and_verify(linear_k, linear, &[&[Fp::zero(), Fp::one(), out]]).unwrap();
+
+        let bits = 64 - n.leading_zeros() as usize;
+        let doubling = DoublingCircuit::<Fp>::new(Value::known(n), bits);
+        let (doubling_rows, doubling_k) = (doubling.rows(), doubling.k());
+        let doubling_report = prover::prove_and_verify(doubling_k, doubling, &[&[Fp::from(n), out]]).unwrap();
+
+        println!(
+            "{:>6} | {:>15} | {:>15} | {:>15} | {:>15}",
+            n,
+            format!("{}/{}", linear_rows, linear_k),
+            format!("{:?}", linear_report.proving_time),
+            format!("{}/{}", doubling_rows, doubling_k),
+            format!("{:?}", doubling_report.proving_time),
+        );
+    }
+}
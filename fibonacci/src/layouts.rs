@@ -0,0 +1,40 @@
+// Proves the same F[n] with every layout of chips/src/fibonacci/layout.rs and compares their cost
+use halo2_proofs::pasta::Fp;
+
+use chips::{
+    fibonacci::{layout::{Folded, Strategy, Tall, Wide}, LayoutCircuit},
+    prover::{self, ProofReport},
+};
+
+fn prove<L: Strategy>(n: usize) -> ProofReport {
+    let circuit = LayoutCircuit::<Fp, L>::new(n);
+    let public_input = circuit.public_input(Fp::one(), Fp::one());
+    prover::prove_and_verify(circuit.k(), circuit, &[&public_input]).unwrap()
+}
+
+fn row<L: Strategy>(n: usize) {
+    let layout = L::LAYOUT;
+    let report = prove::<L>(n);
+    println!(
+        "{:>6} | {:>10} | {:>6} | {:>6} | {:>3} | {:>10} | {:>15}",
+        n,
+        format!("{:?}", layout),
+        layout.advice_columns(),
+        layout.rows(n),
+        report.k,
+        report.proof_size,
+        format!("{:?}", report.proving_time),
+    );
+}
+
+fn main() {
+    // cargo run --release --bin layouts -- [n]
+    let n = std::env::args().nth(1).map(|n| n.parse().expect("n must be a number")).unwrap_or(1000);
+
+    println!("{:>6} | {:>10} | {:>6} | {:>6} | {:>3} | {:>10} | {:>15}", "n", "layout", "advice", "rows", "k", "proof size", "prove");
+    row::<Wide>(n);
+    row::<Tall>(n);
+    row::<Folded<2>>(n);
+    row::<Folded<4>>(n);
+    row::<Folded<8>>(n);
+}